@@ -0,0 +1,280 @@
+/// A growable set of small non-negative integers, one bit per value.
+#[derive(Debug, Clone, Default)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> BitSet {
+        BitSet { words: Vec::new() }
+    }
+
+    /// Create a set that can hold the values `0..capacity` without reallocating.
+    pub fn with_capacity(capacity: usize) -> BitSet {
+        BitSet {
+            words: vec![0; capacity.div_ceil(64)],
+        }
+    }
+
+    /// Insert `value`, returning `true` if it was not already in the set.
+    pub fn insert(&mut self, value: usize) -> bool {
+        let (word, bit) = (value / 64, 1 << (value % 64));
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+
+        let inserted = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        inserted
+    }
+
+    /// Remove `value`, returning `true` if it was in the set.
+    pub fn remove(&mut self, value: usize) -> bool {
+        let (word, bit) = (value / 64, 1 << (value % 64));
+        match self.words.get_mut(word) {
+            Some(w) => {
+                let removed = *w & bit != 0;
+                *w &= !bit;
+                removed
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.words
+            .get(value / 64)
+            .is_some_and(|w| w & (1 << (value % 64)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Iterate over the values in the set in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    pub fn union_with(&mut self, other: &BitSet) {
+        self.combine_with(other, |a, b| a | b);
+    }
+
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        self.combine_with(other, |a, b| a & b);
+    }
+
+    pub fn difference_with(&mut self, other: &BitSet) {
+        self.combine_with(other, |a, b| a & !b);
+    }
+
+    pub fn symmetric_difference_with(&mut self, other: &BitSet) {
+        self.combine_with(other, |a, b| a ^ b);
+    }
+
+    fn combine_with(&mut self, other: &BitSet, f: impl Fn(u64, u64) -> u64) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+
+        for (i, word) in self.words.iter_mut().enumerate() {
+            *word = f(*word, other.words.get(i).copied().unwrap_or(0));
+        }
+    }
+}
+
+impl PartialEq for BitSet {
+    fn eq(&self, other: &BitSet) -> bool {
+        let len = usize::max(self.words.len(), other.words.len());
+        (0..len).all(|i| {
+            self.words.get(i).copied().unwrap_or(0) == other.words.get(i).copied().unwrap_or(0)
+        })
+    }
+}
+
+impl Eq for BitSet {}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> BitSet {
+        let mut set = BitSet::new();
+        for value in iter {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+/// A set of `(x, y)` coordinates within a fixed `width * height` grid, backed by a [`BitSet`].
+#[derive(Debug, Clone)]
+pub struct GridSet {
+    bits: BitSet,
+    width: usize,
+    height: usize,
+}
+
+impl GridSet {
+    pub fn new(width: usize, height: usize) -> GridSet {
+        GridSet {
+            bits: BitSet::with_capacity(width * height),
+            width,
+            height,
+        }
+    }
+
+    fn index(&self, x: i64, y: i64) -> Option<usize> {
+        if x < 0 || x >= self.width as i64 || y < 0 || y >= self.height as i64 {
+            return None;
+        }
+        Some(y as usize * self.width + x as usize)
+    }
+
+    /// Insert a coordinate, returning `true` if it was not already in the set.
+    ///
+    /// Panics if the coordinate is outside the grid.
+    pub fn insert(&mut self, x: i64, y: i64) -> bool {
+        let idx = self
+            .index(x, y)
+            .unwrap_or_else(|| panic!("({}, {}) is outside the grid", x, y));
+        self.bits.insert(idx)
+    }
+
+    pub fn remove(&mut self, x: i64, y: i64) -> bool {
+        match self.index(x, y) {
+            Some(idx) => self.bits.remove(idx),
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        match self.index(x, y) {
+            Some(idx) => self.bits.contains(idx),
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+
+    /// Iterate over the coordinates in the set in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.bits
+            .iter()
+            .map(|i| ((i % self.width) as i64, (i / self.width) as i64))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set = BitSet::new();
+        assert!(set.insert(3));
+        assert!(!set.insert(3));
+        assert!(set.insert(200));
+        assert!(set.contains(3));
+        assert!(set.contains(200));
+        assert!(!set.contains(4));
+        assert!(!set.contains(10_000));
+        assert_eq!(set.len(), 2);
+
+        assert!(set.remove(3));
+        assert!(!set.remove(3));
+        assert!(!set.remove(10_000));
+        assert_eq!(set.iter().collect::<Vec<_>>(), &[200]);
+    }
+
+    #[test]
+    fn test_iter() {
+        let set = [130, 0, 63, 64, 1].into_iter().collect::<BitSet>();
+        assert_eq!(set.iter().collect::<Vec<_>>(), &[0, 1, 63, 64, 130]);
+        assert_eq!(BitSet::with_capacity(100).iter().count(), 0);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = [1, 2, 3, 100].into_iter().collect::<BitSet>();
+        let b = [2, 3, 4].into_iter().collect::<BitSet>();
+
+        let mut union = a.clone();
+        union.union_with(&b);
+        assert_eq!(union.iter().collect::<Vec<_>>(), &[1, 2, 3, 4, 100]);
+
+        let mut intersection = a.clone();
+        intersection.intersect_with(&b);
+        assert_eq!(intersection.iter().collect::<Vec<_>>(), &[2, 3]);
+
+        let mut difference = a.clone();
+        difference.difference_with(&b);
+        assert_eq!(difference.iter().collect::<Vec<_>>(), &[1, 100]);
+
+        let mut symmetric = b.clone();
+        symmetric.symmetric_difference_with(&a);
+        assert_eq!(symmetric.iter().collect::<Vec<_>>(), &[1, 4, 100]);
+    }
+
+    #[test]
+    fn test_eq_ignores_capacity() {
+        let mut a = BitSet::with_capacity(1000);
+        a.insert(5);
+        let b = [5].into_iter().collect::<BitSet>();
+        assert_eq!(a, b);
+        assert!(BitSet::with_capacity(1000).is_empty());
+        assert_eq!(BitSet::with_capacity(1000), BitSet::new());
+    }
+
+    #[test]
+    fn test_grid_set() {
+        let mut grid = GridSet::new(10, 5);
+        assert!(grid.insert(0, 0));
+        assert!(grid.insert(9, 4));
+        assert!(!grid.insert(9, 4));
+        assert!(grid.insert(3, 1));
+        assert_eq!(grid.len(), 3);
+
+        assert!(grid.contains(3, 1));
+        assert!(!grid.contains(1, 3));
+        assert!(!grid.contains(-1, 0));
+        assert!(!grid.contains(10, 0));
+
+        assert_eq!(grid.iter().collect::<Vec<_>>(), &[(0, 0), (3, 1), (9, 4)]);
+
+        assert!(grid.remove(0, 0));
+        assert!(!grid.remove(20, 20));
+        assert_eq!(grid.len(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_grid_set_out_of_bounds() {
+        GridSet::new(10, 5).insert(0, 5);
+    }
+}
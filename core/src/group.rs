@@ -0,0 +1,132 @@
+//! The answers of a group of members, each a set of small integers, combined across members.
+
+use crate::bitset::BitSet;
+
+/// A group whose members each answered a set of values, such as letters mapped to `0..26`.
+/// Values are only bounded by memory, so alphabets of any size can be used.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Group {
+    members: Vec<BitSet>,
+}
+
+impl Group {
+    pub fn new() -> Group {
+        Group {
+            members: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, member: BitSet) {
+        self.members.push(member);
+    }
+
+    pub fn members(&self) -> &[BitSet] {
+        &self.members
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// The values answered by anyone.
+    pub fn union(&self) -> BitSet {
+        self.fold(|acc, member| acc.union_with(member))
+    }
+
+    /// The values answered by everyone, empty for a group without members.
+    pub fn intersection(&self) -> BitSet {
+        self.fold(|acc, member| acc.intersect_with(member))
+    }
+
+    /// The values answered by an odd number of members.
+    pub fn symmetric_difference(&self) -> BitSet {
+        self.fold(|acc, member| acc.symmetric_difference_with(member))
+    }
+
+    /// The values answered by at least `k` members. Values nobody answered are never included,
+    /// so `k = 0` gives the same set as `k = 1`.
+    pub fn at_least(&self, k: usize) -> BitSet {
+        let mut counts = Vec::<usize>::new();
+        for value in self.members.iter().flat_map(|m| m.iter()) {
+            if value >= counts.len() {
+                counts.resize(value + 1, 0);
+            }
+            counts[value] += 1;
+        }
+
+        counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0 && count >= k)
+            .map(|(value, _)| value)
+            .collect()
+    }
+
+    fn fold(&self, f: impl Fn(&mut BitSet, &BitSet)) -> BitSet {
+        let mut members = self.members.iter();
+        let mut acc = members.next().cloned().unwrap_or_default();
+        for member in members {
+            f(&mut acc, member);
+        }
+        acc
+    }
+}
+
+impl FromIterator<BitSet> for Group {
+    fn from_iter<T: IntoIterator<Item = BitSet>>(iter: T) -> Group {
+        Group {
+            members: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn group(members: &[&[usize]]) -> Group {
+        members
+            .iter()
+            .map(|m| m.iter().copied().collect::<BitSet>())
+            .collect()
+    }
+
+    fn values(set: BitSet) -> Vec<usize> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn test_operations() {
+        let g = group(&[&[0, 1, 2, 100], &[1, 2, 3], &[2, 100, 200]]);
+        assert_eq!(g.len(), 3);
+        assert_eq!(values(g.union()), &[0, 1, 2, 3, 100, 200]);
+        assert_eq!(values(g.intersection()), &[2]);
+        assert_eq!(values(g.symmetric_difference()), &[0, 2, 3, 200]);
+        assert_eq!(values(g.at_least(0)), values(g.union()));
+        assert_eq!(values(g.at_least(1)), values(g.union()));
+        assert_eq!(values(g.at_least(2)), &[1, 2, 100]);
+        assert_eq!(values(g.at_least(3)), values(g.intersection()));
+        assert!(g.at_least(4).is_empty());
+    }
+
+    #[test]
+    fn test_empty() {
+        let g = Group::new();
+        assert!(g.is_empty());
+        assert!(g.union().is_empty());
+        assert!(g.intersection().is_empty());
+        assert!(g.symmetric_difference().is_empty());
+        assert!(g.at_least(1).is_empty());
+
+        let mut g = group(&[&[]]);
+        assert!(g.intersection().is_empty());
+        g.push([5].into_iter().collect());
+        assert_eq!(g.members().len(), 2);
+        assert_eq!(values(g.union()), &[5]);
+        assert!(g.intersection().is_empty());
+    }
+}
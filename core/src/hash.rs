@@ -0,0 +1,106 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasherDefault, Hasher};
+
+/// A fast, non-cryptographic hasher using the same mixing as rustc's `FxHasher`.
+///
+/// Much quicker than the default SipHash for small integer and tuple keys, but offers no
+/// protection against collision attacks, which is fine for puzzle input.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+impl FxHasher {
+    #[inline]
+    fn add_to_hash(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+}
+
+impl Hasher for FxHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in chunks.by_ref() {
+            self.add_to_hash(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+
+        let rest = chunks.remainder();
+        if !rest.is_empty() {
+            let mut word = [0u8; 8];
+            word[..rest.len()].copy_from_slice(rest);
+            self.add_to_hash(u64::from_le_bytes(word));
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+pub type FxBuildHasher = BuildHasherDefault<FxHasher>;
+pub type FxHashMap<K, V> = HashMap<K, V, FxBuildHasher>;
+pub type FxHashSet<T> = HashSet<T, FxBuildHasher>;
+
+#[cfg(test)]
+mod test {
+    use std::hash::{BuildHasher, Hash};
+
+    use super::*;
+
+    fn hash<T: Hash>(value: T) -> u64 {
+        FxBuildHasher::default().hash_one(value)
+    }
+
+    #[test]
+    fn test_deterministic() {
+        assert_eq!(hash(42u64), hash(42u64));
+        assert_eq!(hash((1i16, 2i16)), hash((1i16, 2i16)));
+        assert_eq!(hash("abc"), hash("abc"));
+    }
+
+    #[test]
+    fn test_distinguishes_keys() {
+        assert_ne!(hash(1u64), hash(2u64));
+        assert_ne!(hash((1i16, 2i16)), hash((2i16, 1i16)));
+        assert_ne!(hash("abcdefgh1"), hash("abcdefgh2"));
+    }
+
+    #[test]
+    fn test_map() {
+        let mut map = FxHashMap::<u64, u64>::default();
+        for i in 0..1000 {
+            *map.entry(i % 10).or_default() += i;
+        }
+        assert_eq!(map.len(), 10);
+        assert_eq!(map[&0], (0..1000).step_by(10).sum());
+    }
+}
@@ -0,0 +1,579 @@
+//! Minimal, dependency free image encoders for exporting puzzle states.
+//!
+//! [`Image`] can be written as binary PPM or as an uncompressed PNG. [`GifEncoder`] streams
+//! palette based frames into an animated, LZW compressed GIF, only encoding the part of each
+//! frame that changed since the previous one.
+
+use std::io::{self, Write};
+
+use crate::hash::FxHashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, fill: Rgb) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    /// Fill a `size * size` block, used to scale up grid cells.
+    pub fn fill_block(&mut self, x: usize, y: usize, size: usize, color: Rgb) {
+        for py in y * size..(y + 1) * size {
+            for px in x * size..(x + 1) * size {
+                self.set(px, py, color);
+            }
+        }
+    }
+
+    fn rgb_bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.pixels.iter().flat_map(|p| [p.0, p.1, p.2])
+    }
+
+    /// Write the image as a binary (P6) PPM.
+    pub fn write_ppm<W: Write>(&self, mut w: W) -> io::Result<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        w.write_all(&self.rgb_bytes().collect::<Vec<u8>>())
+    }
+
+    /// Write the image as a PNG, using uncompressed deflate blocks.
+    pub fn write_png<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // 8 bit depth, RGB colour, default compression, filter and interlace methods.
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        write_png_chunk(&mut w, b"IHDR", &header)?;
+
+        // Every scanline starts with filter type 0 (none).
+        let mut raw = Vec::with_capacity(self.height * (self.width * 3 + 1));
+        for row in self.pixels.chunks(self.width.max(1)) {
+            raw.push(0);
+            raw.extend(row.iter().flat_map(|p| [p.0, p.1, p.2]));
+        }
+        write_png_chunk(&mut w, b"IDAT", &zlib_stored(&raw))?;
+
+        write_png_chunk(&mut w, b"IEND", &[])
+    }
+}
+
+fn write_png_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+
+    let mut crc = Crc32::new();
+    crc.update(kind);
+    crc.update(data);
+    w.write_all(&crc.finish().to_be_bytes())
+}
+
+/// Wrap `data` in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 65535;
+
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK * 5 + 11);
+    // CM = 8 (deflate), CINFO = 7 (32K window), FCHECK so that the header is a multiple of 31.
+    out.extend_from_slice(&[0x78, 0x01]);
+
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+struct Crc32(u32);
+
+impl Crc32 {
+    fn new() -> Crc32 {
+        Crc32(0xffff_ffff)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.0 ^= *byte as u32;
+            for _ in 0..8 {
+                let mask = (self.0 & 1).wrapping_neg();
+                self.0 = (self.0 >> 1) ^ (0xedb8_8320 & mask);
+            }
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+/// Streaming encoder for animated GIFs with a fixed global palette of at most 256 colours.
+///
+/// Frames are given as one palette index per pixel. Only the bounding box of the pixels that
+/// changed since the previous frame is encoded, and unchanged frames are skipped.
+pub struct GifEncoder<W: Write> {
+    w: W,
+    width: u16,
+    height: u16,
+    min_code_size: u8,
+    delay: u16,
+    previous: Option<Vec<u8>>,
+}
+
+impl<W: Write> GifEncoder<W> {
+    /// Write the GIF header, palette and looping extension. `delay` is in hundredths of a
+    /// second.
+    pub fn new(
+        mut w: W,
+        width: u16,
+        height: u16,
+        palette: &[Rgb],
+        delay: u16,
+    ) -> io::Result<GifEncoder<W>> {
+        assert!(!palette.is_empty() && palette.len() <= 256);
+
+        // The colour table must have 2^(n + 1) entries for some n in 0..8.
+        let bits = usize::max(
+            1,
+            palette.len().next_power_of_two().trailing_zeros() as usize,
+        );
+
+        w.write_all(b"GIF89a")?;
+        w.write_all(&width.to_le_bytes())?;
+        w.write_all(&height.to_le_bytes())?;
+        // Global colour table present, 8 bit colour resolution, table size.
+        w.write_all(&[0xf0 | (bits as u8 - 1), 0, 0])?;
+        for i in 0..1 << bits {
+            let Rgb(r, g, b) = palette.get(i).copied().unwrap_or(Rgb(0, 0, 0));
+            w.write_all(&[r, g, b])?;
+        }
+
+        // Loop forever.
+        w.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+        Ok(GifEncoder {
+            w,
+            width,
+            height,
+            min_code_size: u8::max(2, bits as u8),
+            delay,
+            previous: None,
+        })
+    }
+
+    pub fn add_frame(&mut self, indices: &[u8]) -> io::Result<()> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        assert_eq!(indices.len(), width * height);
+
+        let (left, top, right, bottom) = match &self.previous {
+            None => (0, 0, width, height),
+            Some(previous) => {
+                let rows = (0..height)
+                    .filter(|&y| {
+                        let row = y * width..(y + 1) * width;
+                        previous[row.clone()] != indices[row]
+                    })
+                    .collect::<Vec<_>>();
+                let (top, bottom) = match (rows.first(), rows.last()) {
+                    (Some(&top), Some(&bottom)) => (top, bottom + 1),
+                    _ => return Ok(()),
+                };
+
+                let changed = |x: usize| {
+                    rows.iter()
+                        .any(|y| previous[y * width + x] != indices[y * width + x])
+                };
+                let left = (0..width).find(|&x| changed(x)).unwrap();
+                let right = (0..width).rev().find(|&x| changed(x)).unwrap() + 1;
+                (left, top, right, bottom)
+            }
+        };
+
+        let mut pixels = Vec::with_capacity((right - left) * (bottom - top));
+        for y in top..bottom {
+            pixels.extend_from_slice(&indices[y * width + left..y * width + right]);
+        }
+
+        // Graphic control extension: keep the previous frame underneath, frame delay.
+        self.w.write_all(&[0x21, 0xf9, 0x04, 0x04])?;
+        self.w.write_all(&self.delay.to_le_bytes())?;
+        self.w.write_all(&[0, 0])?;
+
+        // Image descriptor without a local colour table.
+        self.w.write_all(&[0x2c])?;
+        for v in [left, top, right - left, bottom - top] {
+            self.w.write_all(&(v as u16).to_le_bytes())?;
+        }
+        self.w.write_all(&[0])?;
+
+        self.w.write_all(&[self.min_code_size])?;
+        for block in lzw_encode(&pixels, self.min_code_size).chunks(255) {
+            self.w.write_all(&[block.len() as u8])?;
+            self.w.write_all(block)?;
+        }
+        self.w.write_all(&[0])?;
+
+        self.previous = Some(indices.to_vec());
+        Ok(())
+    }
+
+    /// Write the trailer and return the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.w.write_all(&[0x3b])?;
+        self.w.flush()?;
+        Ok(self.w)
+    }
+}
+
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+/// Variable code size LZW as used by GIF, emitting a clear code whenever the table fills up.
+fn lzw_encode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+    const MAX_CODE: u16 = 4096;
+
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+
+    let mut out = BitWriter {
+        out: Vec::new(),
+        buffer: 0,
+        bits: 0,
+    };
+    let mut table = FxHashMap::<(u16, u8), u16>::default();
+    let mut next = end + 1;
+    let mut size = min_code_size + 1;
+
+    out.write(clear, size);
+
+    let mut iter = data.iter();
+    let mut prefix = match iter.next() {
+        Some(first) => *first as u16,
+        None => {
+            out.write(end, size);
+            return out.finish();
+        }
+    };
+
+    for &k in iter {
+        if let Some(&code) = table.get(&(prefix, k)) {
+            prefix = code;
+            continue;
+        }
+
+        out.write(prefix, size);
+
+        table.insert((prefix, k), next);
+        next += 1;
+        if next == MAX_CODE {
+            out.write(clear, size);
+            table.clear();
+            next = end + 1;
+            size = min_code_size + 1;
+        } else if next > 1 << size {
+            size += 1;
+        }
+
+        prefix = k as u16;
+    }
+
+    out.write(prefix, size);
+
+    // The decoder adds one more entry after reading the last code, which can widen the end code.
+    if next + 1 > 1 << size && size < 12 {
+        size += 1;
+    }
+    out.write(end, size);
+    out.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// GIF LZW decoder used to check the encoder output.
+    fn lzw_decode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let end = clear + 1;
+
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut size = min_code_size + 1;
+        let mut previous: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+
+        let (mut buffer, mut bits, mut pos) = (0u32, 0u8, 0usize);
+        loop {
+            while bits < size {
+                buffer |= (data[pos] as u32) << bits;
+                pos += 1;
+                bits += 8;
+            }
+            let code = (buffer & ((1 << size) - 1)) as usize;
+            buffer >>= size;
+            bits -= size;
+
+            if code == clear {
+                table = (0..clear).map(|i| vec![i as u8]).collect();
+                table.push(vec![]);
+                table.push(vec![]);
+                size = min_code_size + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+
+            let entry = match (&previous, code < table.len()) {
+                (_, true) => table[code].clone(),
+                (Some(p), false) => {
+                    let mut e = p.clone();
+                    e.push(p[0]);
+                    e
+                }
+                (None, false) => panic!("invalid code {}", code),
+            };
+
+            if let Some(p) = previous {
+                let mut e = p;
+                e.push(entry[0]);
+                table.push(e);
+                if table.len() == 1 << size && size < 12 {
+                    size += 1;
+                }
+            }
+
+            out.extend_from_slice(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    fn lzw_round_trip(data: &[u8], min_code_size: u8) {
+        let encoded = lzw_encode(data, min_code_size);
+        assert_eq!(lzw_decode(&encoded, min_code_size), data);
+    }
+
+    #[test]
+    fn test_lzw_round_trip() {
+        lzw_round_trip(&[], 2);
+        lzw_round_trip(&[0], 2);
+        lzw_round_trip(&[1, 1, 1, 1, 1, 1, 1, 1, 1, 1], 2);
+        lzw_round_trip(&[0, 1, 2, 3, 0, 1, 2, 3, 3, 3, 2, 1, 0], 2);
+
+        // Long pseudo random input to fill the table and trigger clear codes.
+        let mut x = 12345u32;
+        let data = (0..100_000)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                (x % 16) as u8
+            })
+            .collect::<Vec<u8>>();
+        lzw_round_trip(&data, 4);
+
+        let data = (0..20_000).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+        lzw_round_trip(&data, 8);
+    }
+
+    #[test]
+    fn test_checksums() {
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+        assert_eq!(crc.finish(), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_ppm() {
+        let mut image = Image::new(2, 1, Rgb(0, 0, 0));
+        image.set(1, 0, Rgb(1, 2, 3));
+        let mut out = Vec::new();
+        image.write_ppm(&mut out).unwrap();
+        assert_eq!(out, b"P6\n2 1\n255\n\x00\x00\x00\x01\x02\x03");
+    }
+
+    #[test]
+    fn test_png() {
+        let mut image = Image::new(3, 2, Rgb(10, 20, 30));
+        image.fill_block(1, 0, 1, Rgb(255, 0, 0));
+        let mut out = Vec::new();
+        image.write_png(&mut out).unwrap();
+
+        assert_eq!(&out[..8], b"\x89PNG\r\n\x1a\n");
+
+        // Walk the chunks and check their CRCs.
+        let mut pos = 8;
+        let mut chunks = Vec::new();
+        while pos < out.len() {
+            let len = u32::from_be_bytes(out[pos..pos + 4].try_into().unwrap()) as usize;
+            let kind = &out[pos + 4..pos + 8];
+            let data = &out[pos + 8..pos + 8 + len];
+            let crc = u32::from_be_bytes(out[pos + 8 + len..pos + 12 + len].try_into().unwrap());
+
+            let mut expected = Crc32::new();
+            expected.update(kind);
+            expected.update(data);
+            assert_eq!(crc, expected.finish());
+
+            chunks.push((kind.to_vec(), data.to_vec()));
+            pos += 12 + len;
+        }
+
+        let kinds = chunks.iter().map(|c| &c.0[..]).collect::<Vec<_>>();
+        assert_eq!(kinds, [&b"IHDR"[..], b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+
+        // A single stored block holding both filtered scanlines.
+        let idat = &chunks[1].1;
+        assert_eq!(&idat[..3], &[0x78, 0x01, 0x01]);
+        let raw = &idat[7..idat.len() - 4];
+        assert_eq!(
+            raw,
+            &[
+                0, 10, 20, 30, 255, 0, 0, 10, 20, 30, //
+                0, 10, 20, 30, 10, 20, 30, 10, 20, 30,
+            ]
+        );
+        assert_eq!(&idat[idat.len() - 4..], &adler32(raw).to_be_bytes());
+    }
+
+    #[test]
+    fn test_zlib_stored_blocks() {
+        let data = vec![7u8; 70_000];
+        let stream = zlib_stored(&data);
+        // Header, two block headers, data and checksum.
+        assert_eq!(stream.len(), 2 + 5 + 5 + 70_000 + 4);
+        assert_eq!(stream[2], 0);
+        assert_eq!(&stream[3..7], &[0xff, 0xff, 0x00, 0x00]);
+        assert_eq!(stream[7 + 65535], 1);
+        assert_eq!(
+            zlib_stored(&[]),
+            &[0x78, 0x01, 1, 0, 0, 0xff, 0xff, 0, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_gif() {
+        let palette = [Rgb(0, 0, 0), Rgb(255, 255, 255), Rgb(255, 0, 0)];
+        let mut gif = GifEncoder::new(Vec::new(), 4, 3, &palette, 10).unwrap();
+        let mut frame = vec![0u8; 12];
+        gif.add_frame(&frame).unwrap();
+        // Unchanged frames are skipped.
+        gif.add_frame(&frame).unwrap();
+        frame[6] = 2;
+        frame[7] = 1;
+        gif.add_frame(&frame).unwrap();
+        let out = gif.finish().unwrap();
+
+        assert_eq!(&out[..6], b"GIF89a");
+        assert_eq!(&out[6..10], &[4, 0, 3, 0]);
+        // Four entry colour table, padded with black.
+        assert_eq!(out[10], 0xf1);
+        assert_eq!(&out[13..25], &[0, 0, 0, 255, 255, 255, 255, 0, 0, 0, 0, 0]);
+        assert_eq!(&out[25..44], b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+        assert_eq!(*out.last().unwrap(), 0x3b);
+
+        // Decode the image descriptors and pixel data of both frames.
+        let mut pos = 44;
+        let mut frames = Vec::new();
+        while out[pos] == 0x21 {
+            assert_eq!(&out[pos..pos + 4], &[0x21, 0xf9, 0x04, 0x04]);
+            assert_eq!(&out[pos + 4..pos + 6], &[10, 0]);
+            pos += 8;
+
+            assert_eq!(out[pos], 0x2c);
+            let field = |i: usize| u16::from_le_bytes([out[pos + i], out[pos + i + 1]]);
+            let rect = (field(1), field(3), field(5), field(7));
+            pos += 10;
+
+            let min_code_size = out[pos];
+            pos += 1;
+            let mut data = Vec::new();
+            while out[pos] != 0 {
+                let len = out[pos] as usize;
+                data.extend_from_slice(&out[pos + 1..pos + 1 + len]);
+                pos += 1 + len;
+            }
+            pos += 1;
+
+            frames.push((rect, lzw_decode(&data, min_code_size)));
+        }
+
+        assert_eq!(out.len(), pos + 1);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0], ((0, 0, 4, 3), vec![0; 12]));
+        assert_eq!(frames[1], ((2, 1, 2, 1), vec![2, 1]));
+    }
+}
@@ -0,0 +1,225 @@
+use std::ops::Range;
+
+/// A set of integers stored as sorted, non-overlapping half-open ranges.
+///
+/// Touching ranges are merged on insert, so `0..2` and `2..4` are stored as `0..4`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    ranges: Vec<Range<i64>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    /// Add every value in `range` to the set.
+    pub fn insert(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // Ranges in `i..j` overlap or touch the new range and are merged into it.
+        let i = self.ranges.partition_point(|r| r.end < range.start);
+        let j = self.ranges.partition_point(|r| r.start <= range.end);
+
+        let mut merged = range;
+        if i < j {
+            merged.start = i64::min(merged.start, self.ranges[i].start);
+            merged.end = i64::max(merged.end, self.ranges[j - 1].end);
+        }
+
+        self.ranges.splice(i..j, std::iter::once(merged));
+    }
+
+    /// Remove every value in `range` from the set, splitting ranges that only partially overlap.
+    pub fn remove(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // Ranges in `i..j` overlap the removed range.
+        let i = self.ranges.partition_point(|r| r.end <= range.start);
+        let j = self.ranges.partition_point(|r| r.start < range.end);
+        if i == j {
+            return;
+        }
+
+        let mut rest = Vec::with_capacity(2);
+        if self.ranges[i].start < range.start {
+            rest.push(self.ranges[i].start..range.start);
+        }
+        if self.ranges[j - 1].end > range.end {
+            rest.push(range.end..self.ranges[j - 1].end);
+        }
+
+        self.ranges.splice(i..j, rest);
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= value);
+        self.ranges.get(i).is_some_and(|r| r.contains(&value))
+    }
+
+    /// Total number of values in the set.
+    pub fn len(&self) -> i64 {
+        self.ranges.iter().map(|r| r.end - r.start).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Range<i64>> + '_ {
+        self.ranges.iter()
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut set = self.clone();
+        for range in other.iter() {
+            set.insert(range.clone());
+        }
+        set
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ranges = Vec::new();
+        let mut a = self.ranges.iter().peekable();
+        let mut b = other.ranges.iter().peekable();
+
+        while let (Some(ra), Some(rb)) = (a.peek(), b.peek()) {
+            let start = i64::max(ra.start, rb.start);
+            let end = i64::min(ra.end, rb.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+
+            if ra.end < rb.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+
+        IntervalSet { ranges }
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut set = self.clone();
+        for range in other.iter() {
+            set.remove(range.clone());
+        }
+        set
+    }
+
+    /// Translate the values covered by each `(source, offset)` mapping by `offset`.
+    ///
+    /// Ranges are split at the mapping boundaries. Values not covered by any mapping are kept
+    /// as they are, and where mappings overlap the first one takes precedence.
+    pub fn map_offsets(&self, mappings: &[(Range<i64>, i64)]) -> IntervalSet {
+        let mut unmapped = self.clone();
+        let mut mapped = IntervalSet::new();
+
+        for (source, offset) in mappings {
+            let source = IntervalSet::from_iter([source.clone()]);
+            for range in unmapped.intersection(&source).iter() {
+                mapped.insert((range.start + offset)..(range.end + offset));
+            }
+            unmapped = unmapped.difference(&source);
+        }
+
+        mapped.union(&unmapped)
+    }
+}
+
+impl FromIterator<Range<i64>> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = Range<i64>>>(iter: T) -> IntervalSet {
+        let mut set = IntervalSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod test {
+    use super::*;
+
+    fn set(ranges: &[Range<i64>]) -> IntervalSet {
+        ranges.iter().cloned().collect()
+    }
+
+    fn ranges(set: &IntervalSet) -> Vec<Range<i64>> {
+        set.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_insert_merges() {
+        assert_eq!(ranges(&set(&[0..2, 4..6])), &[0..2, 4..6]);
+        assert_eq!(ranges(&set(&[0..2, 2..4])), &[0..4]);
+        assert_eq!(ranges(&set(&[4..6, 0..2, 1..5])), &[0..6]);
+        assert_eq!(ranges(&set(&[0..10, 3..4])), &[0..10]);
+        assert_eq!(ranges(&set(&[0..1, 3..4, 6..7, 2..6])), &[0..1, 2..7]);
+        assert_eq!(ranges(&set(&[3..3])), &[]);
+    }
+
+    #[test]
+    fn test_remove_splits() {
+        let mut s = set(&[0..10]);
+        s.remove(3..5);
+        assert_eq!(ranges(&s), &[0..3, 5..10]);
+        s.remove(-5..1);
+        assert_eq!(ranges(&s), &[1..3, 5..10]);
+        s.remove(2..6);
+        assert_eq!(ranges(&s), &[1..2, 6..10]);
+        s.remove(10..20);
+        assert_eq!(ranges(&s), &[1..2, 6..10]);
+        s.remove(0..100);
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_contains() {
+        let s = set(&[0..2, 4..6]);
+        assert!(s.contains(0));
+        assert!(s.contains(1));
+        assert!(!s.contains(2));
+        assert!(!s.contains(3));
+        assert!(s.contains(5));
+        assert!(!s.contains(6));
+        assert!(!s.contains(-1));
+    }
+
+    #[test]
+    fn test_len() {
+        assert_eq!(set(&[]).len(), 0);
+        assert_eq!(set(&[0..2, 4..6]).len(), 4);
+        assert_eq!(set(&[-10..10, 5..15]).len(), 25);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[0..5, 10..15]);
+        let b = set(&[3..12]);
+        assert_eq!(ranges(&a.union(&b)), &[0..15]);
+        assert_eq!(ranges(&a.intersection(&b)), &[3..5, 10..12]);
+        assert_eq!(ranges(&a.difference(&b)), &[0..3, 12..15]);
+        assert_eq!(ranges(&b.difference(&a)), &[5..10]);
+    }
+
+    #[test]
+    fn test_map_offsets() {
+        // The seed-to-soil map from the 2023 day 5 example.
+        let seeds = set(&[79..93, 55..68]);
+        let soil = seeds.map_offsets(&[(98..100, 50 - 98), (50..98, 52 - 50)]);
+        assert_eq!(ranges(&soil), &[57..70, 81..95]);
+
+        let split = set(&[0..10]).map_offsets(&[(5..15, 100)]);
+        assert_eq!(ranges(&split), &[0..5, 105..110]);
+
+        let first_wins = set(&[0..10]).map_offsets(&[(0..5, 20), (0..10, 40)]);
+        assert_eq!(ranges(&first_wins), &[20..25, 45..50]);
+    }
+}
@@ -1,44 +1,162 @@
+pub mod bitset;
+pub mod group;
+pub mod hash;
+pub mod image;
+pub mod interval;
+pub mod puzzle;
+pub mod visual;
+
 use std::env;
+use std::path::PathBuf;
+
+use puzzle::puzzle;
+use visual::Mode;
 
 pub type Solution = (Option<fn() -> i64>, Option<fn() -> i64>);
 pub type Year = [Solution; 24];
+/// The source of each day module, from which the puzzle title and statement are read.
+pub type Sources = [Option<&'static str>; 24];
+/// A year specific runner command: name, usage, and a function called with the remaining
+/// arguments.
+pub type Command = (
+    &'static str,
+    &'static str,
+    fn(&[String]) -> Result<(), String>,
+);
+
+fn heading(sources: &Sources, day: usize) -> String {
+    puzzle(sources, day)
+        .map(|p| p.heading())
+        .unwrap_or_else(|| format!("Day {}", day))
+}
 
-fn print_solution(day: usize, solution: &Solution) {
-    let solve = |of: Option<fn() -> i64>| -> String {
-        of.map(|f| f().to_string())
-            .unwrap_or_else(|| "unsolved".to_string())
+fn print_solution(day: usize, solution: &Solution, sources: &Sources) -> Result<(), String> {
+    let solve = |part: usize, of: Option<fn() -> i64>| -> Result<String, String> {
+        match of {
+            Some(f) => {
+                visual::begin(&format!("day{}-part{}", day, part));
+                let result = f();
+                visual::flush()?;
+                Ok(result.to_string())
+            }
+            None => Ok("unsolved".to_string()),
+        }
     };
 
-    let part1 = solve(solution.0);
-    let part2 = solve(solution.1);
+    let part1 = solve(1, solution.0)?;
+    let part2 = solve(2, solution.1)?;
 
     println!(
-        "\tDay: {:2}, part1: {:>10}, part2: {:>10}",
-        day, part1, part2
+        "\t{:<34} part1: {:>10}, part2: {:>10}",
+        heading(sources, day) + ",",
+        part1,
+        part2
     );
+    Ok(())
+}
+
+/// List every day that has a puzzle or a solution, with a star per solved part.
+fn print_index(year: &Year, sources: &Sources) {
+    for (i, (part1, part2)) in year.iter().enumerate() {
+        let day = i + 1;
+        let stars = part1.is_some() as usize + part2.is_some() as usize;
+        if stars == 0 && puzzle(sources, day).is_none() {
+            continue;
+        }
+        println!("\t{:<2} {}", "*".repeat(stars), heading(sources, day));
+    }
+}
+
+/// Print the heading and statement of the puzzle for `day`.
+fn print_puzzle(sources: &Sources, day: usize) -> Result<(), String> {
+    let puzzle = puzzle(sources, day).ok_or(format!("No puzzle statement for day {}", day))?;
+    println!("{}", puzzle.heading());
+    if !puzzle.statement.is_empty() {
+        println!("\n{}", puzzle.statement);
+    }
+    Ok(())
+}
+
+static USAGE: &str =
+    "Usage: advent-of-code [--visualize | --animate[=MS]] [--export=DIR] [DAY | index [DAY]]";
+
+fn usage(commands: &[Command]) -> String {
+    let mut usage = USAGE.to_string();
+    for (name, args, _) in commands {
+        usage += &format!("\n       advent-of-code {} {}", name, args);
+    }
+    usage
 }
 
-pub fn menu(year: &Year) -> Result<(), String> {
-    let args: Vec<String> = env::args().collect();
-    if !(1..=2).contains(&args.len()) {
-        return Err(format!("Usage: advent-of-code <DAY>"));
+fn parse_day(s: &str) -> Result<usize, String> {
+    let day = s
+        .parse::<usize>()
+        .map_err(|e| format!("Invalid date: {}", e))?;
+
+    match day {
+        1..=24 => Ok(day),
+        _ => Err(format!("Day {} out of range, must be between 1..24.", day)),
     }
+}
 
-    if args.len() == 2 {
-        let day = args[1]
-            .parse::<usize>()
-            .map_err(|e| format!("Invalid date: {}", e))?;
+fn parse_animate_delay(s: &str) -> Result<u64, String> {
+    s.parse::<u64>()
+        .map_err(|e| format!("Invalid animation delay: {}", e))
+}
 
-        let day = match day {
-            1..=24 => Ok(day),
-            _ => Err(format!("Day {} out of range, must be between 1..24.", day)),
-        }?;
+pub fn menu(year: &Year, sources: &Sources) -> Result<(), String> {
+    menu_with_commands(year, sources, &[])
+}
 
+/// Like [`menu`], also dispatching to `commands` when the first argument names one.
+pub fn menu_with_commands(
+    year: &Year,
+    sources: &Sources,
+    commands: &[Command],
+) -> Result<(), String> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if let Some((_, _, run)) = args
+        .first()
+        .and_then(|first| commands.iter().find(|(name, _, _)| name == first))
+    {
+        return run(&args[1..]);
+    }
+
+    let mut day = None;
+    let mut index = false;
+    let mut mode = Mode::Off;
+    let mut export = None;
+
+    for arg in args {
+        match arg.as_str() {
+            "--visualize" => mode = Mode::Final,
+            "--animate" => mode = Mode::Animate(50),
+            s if s.starts_with("--animate=") => {
+                mode = Mode::Animate(parse_animate_delay(&s["--animate=".len()..])?)
+            }
+            s if s.starts_with("--export=") => {
+                export = Some(PathBuf::from(&s["--export=".len()..]))
+            }
+            "index" if day.is_none() => index = true,
+            s if day.is_none() && !s.starts_with("--") => day = Some(parse_day(s)?),
+            _ => return Err(usage(commands)),
+        }
+    }
+
+    visual::set_mode(mode)?;
+    visual::set_export(export)?;
+
+    if index {
+        match day {
+            Some(day) => print_puzzle(sources, day)?,
+            None => print_index(year, sources),
+        }
+    } else if let Some(day) = day {
         let solution = year[day - 1];
-        print_solution(day, &solution)
+        print_solution(day, &solution, sources)?;
     } else {
         for (day, solution) in year.iter().enumerate() {
-            print_solution(day + 1, solution)
+            print_solution(day + 1, solution, sources)?;
         }
     };
 
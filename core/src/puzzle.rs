@@ -0,0 +1,180 @@
+//! Puzzle metadata read from the `//!` module docs of each day.
+//!
+//! Every year passes the source of its day modules to the runner through `include_str!`, and the
+//! leading doc comment is parsed on demand. The first line is expected to be the puzzle heading,
+//! `# Day 8: Handheld Halting`, and the rest of the comment is the puzzle statement.
+
+use crate::{Sources, Year};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub day: usize,
+    pub title: String,
+    pub statement: String,
+}
+
+impl Puzzle {
+    /// Parse the leading `//!` comment of a source file, returns `None` if the file does not
+    /// start with a `# Day N: Title` heading.
+    pub fn parse(source: &str) -> Option<Puzzle> {
+        let mut docs = source
+            .lines()
+            .map_while(|l| l.strip_prefix("//!"))
+            .map(|l| l.strip_prefix(' ').unwrap_or(l));
+
+        let (day, title) = docs.next()?.strip_prefix("# Day ")?.split_once(':')?;
+        let day = day.trim().parse::<usize>().ok()?;
+        let title = title.trim();
+        if title.is_empty() {
+            return None;
+        }
+
+        let statement = docs.collect::<Vec<_>>().join("\n").trim().to_string();
+
+        Some(Puzzle {
+            day,
+            title: title.to_string(),
+            statement,
+        })
+    }
+
+    /// `Day 8: Handheld Halting`
+    pub fn heading(&self) -> String {
+        format!("Day {}: {}", self.day, self.title)
+    }
+}
+
+/// Parse the puzzle for `day` (1-based), if its source is known and documented.
+pub fn puzzle(sources: &Sources, day: usize) -> Option<Puzzle> {
+    sources
+        .get(day - 1)
+        .copied()
+        .flatten()
+        .and_then(Puzzle::parse)
+}
+
+/// Place the `(day, source)` pairs at their day, used by [`sources!`](crate::sources) to build the
+/// [`Sources`] of a year at compile time.
+pub const fn sources(days: &[(usize, &'static str)]) -> Sources {
+    let mut sources = [None; 24];
+    let mut i = 0;
+    while i < days.len() {
+        sources[days[i].0 - 1] = Some(days[i].1);
+        i += 1;
+    }
+    sources
+}
+
+/// Declare `static $sources: Sources` holding the source of the `src/dayN.rs` module of each
+/// listed day, and a `test_puzzle_titles` test checking their titles against `$solutions`.
+///
+/// ```text
+/// advent_of_code_core::sources!(SOURCES, SOLUTIONS, [1, 2, 3]);
+/// ```
+#[macro_export]
+macro_rules! sources {
+    ($sources:ident, $solutions:ident, [$($day:literal),* $(,)?]) => {
+        pub static $sources: $crate::Sources = $crate::puzzle::sources(&[$((
+            $day,
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/day", $day, ".rs")),
+        )),*]);
+
+        #[test]
+        fn test_puzzle_titles() {
+            $crate::puzzle::check_titles(&$solutions, &$sources).unwrap();
+        }
+    };
+}
+
+/// Check that every day with a solution has a puzzle title matching its day number.
+pub fn check_titles(year: &Year, sources: &Sources) -> Result<(), String> {
+    let missing = year
+        .iter()
+        .enumerate()
+        .filter(|(_, (part1, part2))| part1.is_some() || part2.is_some())
+        .map(|(i, _)| i + 1)
+        .filter(|&day| puzzle(sources, day).is_none_or(|p| p.day != day))
+        .map(|day| day.to_string())
+        .collect::<Vec<_>>();
+
+    match missing.is_empty() {
+        true => Ok(()),
+        false => Err(format!(
+            "Missing or mismatched puzzle title for day {}",
+            missing.join(", ")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static SOURCE: &str = "//! # Day 8: Handheld Halting
+//!
+//! Your flight to the major airline hub reaches cruising altitude without incident.
+//!
+//!   - indented
+//!
+use std::io;
+
+//! not part of the module docs
+";
+
+    #[test]
+    fn test_parse() {
+        let puzzle = Puzzle::parse(SOURCE).unwrap();
+        assert_eq!(puzzle.day, 8);
+        assert_eq!(puzzle.title, "Handheld Halting");
+        assert_eq!(puzzle.heading(), "Day 8: Handheld Halting");
+        assert_eq!(
+            puzzle.statement,
+            "Your flight to the major airline hub reaches cruising altitude without incident.\n\n  - indented"
+        );
+
+        let title_only = Puzzle::parse("//! # Day 12: Rain Risk\n\nfn main() {}\n").unwrap();
+        assert_eq!(title_only.heading(), "Day 12: Rain Risk");
+        assert_eq!(title_only.statement, "");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(Puzzle::parse(""), None);
+        assert_eq!(Puzzle::parse("fn load() {}\n"), None);
+        assert_eq!(Puzzle::parse("//! Day 4: Passport Processing\n"), None);
+        assert_eq!(Puzzle::parse("//! # Day x: Title\n"), None);
+        assert_eq!(Puzzle::parse("//! # Day 1:\n"), None);
+        assert_eq!(Puzzle::parse("use a;\n//! # Day 1: Title\n"), None);
+    }
+
+    #[test]
+    fn test_sources() {
+        const SOURCES: Sources = sources(&[(1, "day 1"), (24, "day 24")]);
+        assert_eq!(SOURCES[0], Some("day 1"));
+        assert_eq!(SOURCES[1..23], [None; 22]);
+        assert_eq!(SOURCES[23], Some("day 24"));
+    }
+
+    #[test]
+    fn test_check_titles() {
+        fn solve() -> i64 {
+            0
+        }
+
+        let mut year: Year = [(None, None); 24];
+        let mut sources: Sources = [None; 24];
+        assert_eq!(check_titles(&year, &sources), Ok(()));
+
+        year[0] = (Some(solve), None);
+        year[2] = (Some(solve), Some(solve));
+        sources[0] = Some("//! # Day 1: Report Repair\n");
+        sources[2] = Some("//! # Day 2: Password Philosophy\n");
+        assert_eq!(
+            check_titles(&year, &sources),
+            Err("Missing or mismatched puzzle title for day 3".to_string())
+        );
+
+        sources[2] = Some("//! # Day 3: Toboggan Trajectory\n");
+        assert_eq!(check_titles(&year, &sources), Ok(()));
+    }
+}
@@ -0,0 +1,524 @@
+//! Terminal rendering of grid based puzzle states.
+//!
+//! Solutions build a [`Frame`] and pass it to [`frame`], which renders it according to the
+//! [`Mode`] selected on the command line. Without the `visualize` feature [`enabled`] is always
+//! `false` and the hooks do nothing, so frames are never built in normal runs.
+//!
+//! Frames can also be exported with [`set_export`]: every frame of a part is appended to an
+//! animated GIF, and the last frame is written as PNG and PPM.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::image::{Image, Rgb};
+
+#[cfg(feature = "visualize")]
+use std::fs::{self, File};
+#[cfg(feature = "visualize")]
+use std::io::BufWriter;
+#[cfg(feature = "visualize")]
+use std::sync::Mutex;
+#[cfg(feature = "visualize")]
+use std::time::Duration;
+
+#[cfg(feature = "visualize")]
+use crate::image::GifEncoder;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Nothing is rendered.
+    Off,
+    /// Only the last frame of each part is printed.
+    Final,
+    /// Every frame is printed, waiting the given number of milliseconds between frames.
+    Animate(u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Gray,
+}
+
+impl Color {
+    const ALL: [Color; 8] = [
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+        Color::Gray,
+    ];
+
+    fn ansi_code(self) -> u8 {
+        match self {
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+            Color::Gray => 90,
+        }
+    }
+
+    pub fn rgb(self) -> Rgb {
+        match self {
+            Color::Red => Rgb(220, 50, 47),
+            Color::Green => Rgb(80, 200, 80),
+            Color::Yellow => Rgb(240, 200, 40),
+            Color::Blue => Rgb(60, 110, 230),
+            Color::Magenta => Rgb(210, 60, 200),
+            Color::Cyan => Rgb(40, 200, 210),
+            Color::White => Rgb(250, 250, 250),
+            Color::Gray => Rgb(120, 120, 120),
+        }
+    }
+}
+
+/// Image colour of uncoloured `.` cells.
+const BACKGROUND: Rgb = Rgb(20, 20, 30);
+/// Image colour of any other uncoloured cell.
+const FOREGROUND: Rgb = Rgb(190, 190, 190);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub color: Option<Color>,
+}
+
+/// A grid of characters with optional colours, drawn on top of each other in call order.
+///
+/// Coordinates outside the grid are ignored, so callers can draw without bounds checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    /// Create an empty frame filled with `.`.
+    pub fn new(width: usize, height: usize) -> Frame {
+        let cell = Cell {
+            ch: '.',
+            color: None,
+        };
+        Frame {
+            width,
+            height,
+            cells: vec![cell; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, x: i64, y: i64) -> Option<usize> {
+        if x < 0 || x >= self.width as i64 || y < 0 || y >= self.height as i64 {
+            return None;
+        }
+        Some(y as usize * self.width + x as usize)
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> Option<Cell> {
+        self.index(x, y).map(|i| self.cells[i])
+    }
+
+    /// Set the character of a cell, keeping its colour.
+    pub fn set(&mut self, x: i64, y: i64, ch: char) {
+        if let Some(i) = self.index(x, y) {
+            self.cells[i].ch = ch;
+        }
+    }
+
+    /// Set the colour of a cell, keeping its character.
+    pub fn tint(&mut self, x: i64, y: i64, color: Color) {
+        if let Some(i) = self.index(x, y) {
+            self.cells[i].color = Some(color);
+        }
+    }
+
+    /// Set both the character and the colour of a cell.
+    pub fn mark(&mut self, x: i64, y: i64, ch: char, color: Color) {
+        if let Some(i) = self.index(x, y) {
+            self.cells[i] = Cell {
+                ch,
+                color: Some(color),
+            };
+        }
+    }
+
+    /// Mark every cell along a path.
+    pub fn path(&mut self, points: impl IntoIterator<Item = (i64, i64)>, ch: char, color: Color) {
+        for (x, y) in points {
+            self.mark(x, y, ch, color);
+        }
+    }
+
+    /// The colours used by exported images, indexed by [`Frame::to_indices`].
+    pub fn palette() -> Vec<Rgb> {
+        [BACKGROUND, FOREGROUND]
+            .into_iter()
+            .chain(Color::ALL.iter().map(|c| c.rgb()))
+            .collect()
+    }
+
+    fn palette_index(cell: &Cell) -> u8 {
+        match cell.color {
+            Some(color) => 2 + Color::ALL.iter().position(|c| *c == color).unwrap() as u8,
+            None if cell.ch == '.' => 0,
+            None => 1,
+        }
+    }
+
+    /// Convert to one [`Frame::palette`] index per pixel, each cell covering `scale * scale`
+    /// pixels.
+    pub fn to_indices(&self, scale: usize) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.cells.len() * scale * scale);
+        for row in self.cells.chunks(self.width.max(1)) {
+            let line = row
+                .iter()
+                .flat_map(|cell| std::iter::repeat_n(Frame::palette_index(cell), scale))
+                .collect::<Vec<u8>>();
+            for _ in 0..scale {
+                out.extend_from_slice(&line);
+            }
+        }
+        out
+    }
+
+    pub fn to_image(&self, scale: usize) -> Image {
+        let palette = Frame::palette();
+        let mut image = Image::new(self.width * scale, self.height * scale, BACKGROUND);
+        for (i, cell) in self.cells.iter().enumerate() {
+            let color = palette[Frame::palette_index(cell) as usize];
+            image.fill_block(i % self.width, i / self.width, scale, color);
+        }
+        image
+    }
+
+    /// Render the frame with ANSI colour escape codes.
+    pub fn render(&self) -> String {
+        let mut out = String::with_capacity(self.cells.len() * 2);
+        for row in self.cells.chunks(self.width.max(1)) {
+            let mut current = None;
+            for cell in row {
+                if cell.color != current {
+                    match cell.color {
+                        Some(color) => out.push_str(&format!("\x1b[{}m", color.ansi_code())),
+                        None => out.push_str("\x1b[0m"),
+                    }
+                    current = cell.color;
+                }
+                out.push(cell.ch);
+            }
+            if current.is_some() {
+                out.push_str("\x1b[0m");
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                write!(f, "{}", cell.ch)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "visualize")]
+static MODE: Mutex<Mode> = Mutex::new(Mode::Off);
+
+#[cfg(feature = "visualize")]
+static LAST: Mutex<Option<Frame>> = Mutex::new(None);
+
+/// Number of pixels per cell side in exported images.
+#[cfg(feature = "visualize")]
+const SCALE: usize = 4;
+
+/// Hundredths of a second between frames of exported animations.
+#[cfg(feature = "visualize")]
+const GIF_DELAY: u16 = 5;
+
+#[cfg(feature = "visualize")]
+struct Export {
+    dir: PathBuf,
+    name: String,
+    gif: Option<GifEncoder<BufWriter<File>>>,
+    size: (usize, usize),
+    last: Option<Frame>,
+    error: Option<String>,
+}
+
+#[cfg(feature = "visualize")]
+impl Export {
+    fn add(&mut self, frame: &Frame) -> Result<(), String> {
+        let size = (frame.width * SCALE, frame.height * SCALE);
+        if self.gif.is_none() {
+            let path = self.dir.join(format!("{}.gif", self.name));
+            let file = File::create(&path)
+                .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+            let (width, height) = (u16::try_from(size.0), u16::try_from(size.1));
+            let (Ok(width), Ok(height)) = (width, height) else {
+                return Err(format!("Frame too large to export: {}x{}", size.0, size.1));
+            };
+
+            let gif = GifEncoder::new(
+                BufWriter::new(file),
+                width,
+                height,
+                &Frame::palette(),
+                GIF_DELAY,
+            )
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            self.gif = Some(gif);
+            self.size = size;
+        }
+
+        if size != self.size {
+            return Err(format!(
+                "Frame size changed from {}x{} to {}x{} during {}",
+                self.size.0, self.size.1, size.0, size.1, self.name
+            ));
+        }
+
+        self.gif
+            .as_mut()
+            .unwrap()
+            .add_frame(&frame.to_indices(SCALE))
+            .map_err(|e| format!("Failed to write {}.gif: {}", self.name, e))
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        if let Some(error) = self.error.take() {
+            self.gif = None;
+            self.last = None;
+            return Err(error);
+        }
+
+        if let Some(gif) = self.gif.take() {
+            gif.finish()
+                .map_err(|e| format!("Failed to write {}.gif: {}", self.name, e))?;
+        }
+
+        if let Some(frame) = self.last.take() {
+            let image = frame.to_image(SCALE);
+            let write = |ext: &str, f: &dyn Fn(File) -> std::io::Result<()>| {
+                let path = self.dir.join(format!("{}.{}", self.name, ext));
+                File::create(&path)
+                    .and_then(f)
+                    .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+            };
+            write("png", &|file| image.write_png(BufWriter::new(file)))?;
+            write("ppm", &|file| image.write_ppm(BufWriter::new(file)))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "visualize")]
+static EXPORT: Mutex<Option<Export>> = Mutex::new(None);
+
+/// Select how frames are rendered. Fails unless built with the `visualize` feature.
+#[cfg(feature = "visualize")]
+pub fn set_mode(mode: Mode) -> Result<(), String> {
+    *MODE.lock().unwrap() = mode;
+    Ok(())
+}
+
+#[cfg(not(feature = "visualize"))]
+static NOT_SUPPORTED: &str =
+    "Visualisation is not supported by this build, rebuild with `--features visualize`.";
+
+#[cfg(not(feature = "visualize"))]
+pub fn set_mode(mode: Mode) -> Result<(), String> {
+    match mode {
+        Mode::Off => Ok(()),
+        _ => Err(NOT_SUPPORTED.to_string()),
+    }
+}
+
+/// Export frames as images to `dir`, creating it if needed. Fails unless built with the
+/// `visualize` feature.
+#[cfg(feature = "visualize")]
+pub fn set_export(dir: Option<PathBuf>) -> Result<(), String> {
+    let export = match dir {
+        Some(dir) => {
+            fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            Some(Export {
+                dir,
+                name: String::new(),
+                gif: None,
+                size: (0, 0),
+                last: None,
+                error: None,
+            })
+        }
+        None => None,
+    };
+    *EXPORT.lock().unwrap() = export;
+    Ok(())
+}
+
+#[cfg(not(feature = "visualize"))]
+pub fn set_export(dir: Option<PathBuf>) -> Result<(), String> {
+    match dir {
+        None => Ok(()),
+        Some(_) => Err(NOT_SUPPORTED.to_string()),
+    }
+}
+
+/// Name the files exported for the following frames, called by the runner before each part.
+#[cfg(feature = "visualize")]
+pub fn begin(name: &str) {
+    if let Some(export) = EXPORT.lock().unwrap().as_mut() {
+        export.name = name.to_string();
+    }
+}
+
+#[cfg(not(feature = "visualize"))]
+#[inline(always)]
+pub fn begin(_name: &str) {}
+
+/// Returns `true` if frames passed to [`frame`] will be rendered or exported.
+#[cfg(feature = "visualize")]
+pub fn enabled() -> bool {
+    *MODE.lock().unwrap() != Mode::Off || EXPORT.lock().unwrap().is_some()
+}
+
+#[cfg(not(feature = "visualize"))]
+#[inline(always)]
+pub const fn enabled() -> bool {
+    false
+}
+
+/// Hand a frame to the renderer. The frame is only built if visualisation is enabled.
+#[cfg(feature = "visualize")]
+pub fn frame(f: impl FnOnce() -> Frame) {
+    if !enabled() {
+        return;
+    }
+    let frame = f();
+
+    if let Some(export) = EXPORT.lock().unwrap().as_mut() {
+        // Errors are reported by `flush` once the part is done.
+        if export.error.is_none() {
+            export.error = export.add(&frame).err();
+        }
+        export.last = Some(frame.clone());
+    }
+
+    let mode = *MODE.lock().unwrap();
+    match mode {
+        Mode::Off => {}
+        Mode::Final => *LAST.lock().unwrap() = Some(frame),
+        Mode::Animate(delay) => {
+            // Clear the screen and move the cursor to the top left corner before each frame.
+            print!("\x1b[2J\x1b[H{}", frame.render());
+            std::thread::sleep(Duration::from_millis(delay));
+        }
+    }
+}
+
+#[cfg(not(feature = "visualize"))]
+#[inline(always)]
+pub fn frame(_f: impl FnOnce() -> Frame) {}
+
+/// Print the last frame in [`Mode::Final`] and finish exported files, called by the runner
+/// after each part.
+#[cfg(feature = "visualize")]
+pub fn flush() -> Result<(), String> {
+    if let Some(frame) = LAST.lock().unwrap().take() {
+        print!("{}", frame.render());
+    }
+
+    match EXPORT.lock().unwrap().as_mut() {
+        Some(export) => export.finish(),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(feature = "visualize"))]
+#[inline(always)]
+pub fn flush() -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let mut frame = Frame::new(3, 2);
+        frame.set(0, 0, '#');
+        frame.mark(2, 1, '^', Color::Red);
+        frame.set(3, 0, '#');
+        frame.set(-1, 0, '#');
+        assert_eq!(frame.to_string(), "#..\n..^\n");
+    }
+
+    #[test]
+    fn test_overlays() {
+        let mut frame = Frame::new(4, 1);
+        frame.path([(0, 0), (1, 0), (2, 0)], 'X', Color::Yellow);
+        frame.tint(1, 0, Color::Red);
+        frame.set(2, 0, '#');
+
+        assert_eq!(frame.to_string(), "XX#.\n");
+        assert_eq!(frame.get(1, 0).unwrap().color, Some(Color::Red));
+        assert_eq!(frame.get(2, 0).unwrap().color, Some(Color::Yellow));
+        assert_eq!(frame.get(3, 0).unwrap().color, None);
+        assert_eq!(frame.get(4, 0), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut frame = Frame::new(3, 2);
+        frame.mark(0, 0, 'a', Color::Red);
+        frame.mark(1, 0, 'b', Color::Red);
+        frame.mark(1, 1, 'c', Color::Green);
+        assert_eq!(frame.render(), "\x1b[31mab\x1b[0m.\n.\x1b[32mc\x1b[0m.\n");
+    }
+
+    #[test]
+    fn test_to_image() {
+        let mut frame = Frame::new(2, 1);
+        frame.set(0, 0, '#');
+        frame.tint(1, 0, Color::Blue);
+
+        let palette = Frame::palette();
+        assert_eq!(frame.to_indices(2), &[1, 1, 5, 5, 1, 1, 5, 5]);
+        assert_eq!(palette[5], Color::Blue.rgb());
+
+        let image = frame.to_image(2);
+        assert_eq!((image.width(), image.height()), (4, 2));
+        assert_eq!(image.get(1, 1), FOREGROUND);
+        assert_eq!(image.get(2, 0), Color::Blue.rgb());
+        assert_eq!(Frame::new(1, 1).to_image(1).get(0, 0), BACKGROUND);
+    }
+}
@@ -0,0 +1,538 @@
+//! Virtual machine for the handheld game console from day 8.
+//!
+//! Opcodes are looked up in an [`OpcodeTable`], so new instructions only need a mnemonic and a
+//! function updating the [`State`]. A [`Machine`] runs a program one instruction at a time,
+//! stopping when the program terminates, jumps out of bounds, is about to repeat an instruction
+//! or hits a breakpoint, and can record a trace of every executed instruction.
+
+use std::io::{self, BufRead, Write};
+
+use advent_of_code_core::bitset::BitSet;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct State {
+    pub pc: i64,
+    pub acc: i64,
+}
+
+/// Execute an instruction with the given argument, responsible for advancing `pc`.
+pub type Exec = fn(&mut State, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    /// Index into the [`OpcodeTable`] the program was assembled with.
+    pub op: u8,
+    pub arg: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OpcodeTable {
+    opcodes: Vec<(&'static str, Exec)>,
+}
+
+impl OpcodeTable {
+    pub fn new() -> OpcodeTable {
+        OpcodeTable {
+            opcodes: Vec::new(),
+        }
+    }
+
+    /// The instruction set of the handheld console: `acc`, `jmp` and `nop`.
+    pub fn handheld() -> OpcodeTable {
+        let mut table = OpcodeTable::new();
+        table.register("acc", |s, arg| {
+            s.acc += arg;
+            s.pc += 1;
+        });
+        table.register("jmp", |s, arg| s.pc += arg);
+        table.register("nop", |s, _| s.pc += 1);
+        table
+    }
+
+    /// Add an opcode, replacing any existing opcode with the same mnemonic.
+    pub fn register(&mut self, mnemonic: &'static str, exec: Exec) -> u8 {
+        match self.lookup(mnemonic) {
+            Some(op) => {
+                self.opcodes[op as usize].1 = exec;
+                op
+            }
+            None => {
+                assert!(
+                    self.opcodes.len() < u8::MAX as usize,
+                    "opcode table is full"
+                );
+                self.opcodes.push((mnemonic, exec));
+                (self.opcodes.len() - 1) as u8
+            }
+        }
+    }
+
+    pub fn lookup(&self, mnemonic: &str) -> Option<u8> {
+        self.opcodes
+            .iter()
+            .position(|(m, _)| *m == mnemonic)
+            .map(|op| op as u8)
+    }
+
+    pub fn mnemonic(&self, op: u8) -> &'static str {
+        self.opcodes[op as usize].0
+    }
+
+    fn exec(&self, op: u8) -> Exec {
+        self.opcodes[op as usize].1
+    }
+
+    /// The `pc` after executing `instruction` at `pc`, assuming jumps do not depend on `acc`.
+    pub fn successor(&self, instruction: Instruction, pc: i64) -> i64 {
+        let mut state = State { pc, acc: 0 };
+        (self.exec(instruction.op))(&mut state, instruction.arg);
+        state.pc
+    }
+
+    /// Parse one instruction per line, e.g. `jmp -3`.
+    pub fn assemble(&self, source: &str) -> Result<Vec<Instruction>, String> {
+        source
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let (mnemonic, arg) = line
+                    .trim()
+                    .split_once(' ')
+                    .ok_or_else(|| format!("Line {}: expected 'op arg', got '{}'", i + 1, line))?;
+                let op = self
+                    .lookup(mnemonic)
+                    .ok_or_else(|| format!("Line {}: unknown opcode '{}'", i + 1, mnemonic))?;
+                let arg = arg
+                    .trim()
+                    .parse::<i64>()
+                    .map_err(|e| format!("Line {}: invalid argument '{}': {}", i + 1, arg, e))?;
+                Ok(Instruction { op, arg })
+            })
+            .collect()
+    }
+
+    pub fn disassemble_one(&self, instruction: Instruction) -> String {
+        format!("{} {:+}", self.mnemonic(instruction.op), instruction.arg)
+    }
+
+    /// The inverse of [`OpcodeTable::assemble`].
+    pub fn disassemble(&self, program: &[Instruction]) -> String {
+        program
+            .iter()
+            .map(|i| self.disassemble_one(*i) + "\n")
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    /// `pc` is one past the last instruction.
+    Terminated,
+    /// The instruction at `pc` has already been executed.
+    Loop,
+    /// `pc` is outside the program.
+    OutOfBounds,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    Exit(Exit),
+    /// The instruction at `pc` has a breakpoint and has not been executed yet.
+    Breakpoint,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub pc: usize,
+    pub instruction: Instruction,
+    /// The state after executing the instruction.
+    pub state: State,
+}
+
+pub struct Machine<'a> {
+    table: &'a OpcodeTable,
+    program: &'a [Instruction],
+    state: State,
+    executed: BitSet,
+    breakpoints: BitSet,
+    trace: Option<Vec<TraceEntry>>,
+}
+
+impl<'a> Machine<'a> {
+    pub fn new(table: &'a OpcodeTable, program: &'a [Instruction]) -> Machine<'a> {
+        Machine {
+            table,
+            program,
+            state: State::default(),
+            executed: BitSet::with_capacity(program.len()),
+            breakpoints: BitSet::new(),
+            trace: None,
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Restart the program, keeping breakpoints and clearing the trace.
+    pub fn reset(&mut self) {
+        self.state = State::default();
+        self.executed.clear();
+        if let Some(trace) = self.trace.as_mut() {
+            trace.clear();
+        }
+    }
+
+    /// Start recording every executed instruction.
+    pub fn enable_trace(&mut self) {
+        self.trace.get_or_insert_with(Vec::new);
+    }
+
+    pub fn trace(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or(&[])
+    }
+
+    /// Set a breakpoint, returns `false` if it was already set.
+    pub fn set_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.insert(pc)
+    }
+
+    /// Clear a breakpoint, returns `false` if it was not set.
+    pub fn clear_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.remove(pc)
+    }
+
+    pub fn has_breakpoint(&self, pc: usize) -> bool {
+        self.breakpoints.contains(pc)
+    }
+
+    /// Why the machine can not execute the instruction at `pc`, if it can't.
+    pub fn exit(&self) -> Option<Exit> {
+        let len = self.program.len() as i64;
+        if self.state.pc == len {
+            Some(Exit::Terminated)
+        } else if self.state.pc < 0 || self.state.pc > len {
+            Some(Exit::OutOfBounds)
+        } else if self.executed.contains(self.state.pc as usize) {
+            Some(Exit::Loop)
+        } else {
+            None
+        }
+    }
+
+    /// Execute the instruction at `pc`.
+    pub fn step(&mut self) -> Result<(), Exit> {
+        if let Some(exit) = self.exit() {
+            return Err(exit);
+        }
+
+        let pc = self.state.pc as usize;
+        let instruction = self.program[pc];
+        self.executed.insert(pc);
+        (self.table.exec(instruction.op))(&mut self.state, instruction.arg);
+
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(TraceEntry {
+                pc,
+                instruction,
+                state: self.state,
+            });
+        }
+        Ok(())
+    }
+
+    /// Run until the machine exits or reaches a breakpoint. The current instruction is always
+    /// executed, so continuing from a breakpoint makes progress.
+    pub fn run(&mut self) -> Stop {
+        loop {
+            if let Err(exit) = self.step() {
+                return Stop::Exit(exit);
+            }
+            if self.exit().is_none() && self.has_breakpoint(self.state.pc as usize) {
+                return Stop::Breakpoint;
+            }
+        }
+    }
+}
+
+static HELP: &str = "\
+Commands:
+  s, step [N]      execute N instructions, default 1
+  c, continue      run until a breakpoint or the program exits
+  b, break PC      set a breakpoint
+  d, delete PC     clear a breakpoint
+  l, list [N]      show N instructions around pc, default 3
+  t, trace [N]     show the last N executed instructions, default 10
+  x, dump          print the whole program
+  p, print         show pc and acc
+  r, reset         restart the program
+  q, quit          leave the debugger
+";
+
+/// Interactive debugger reading one command per line from `input`.
+pub fn debug(machine: &mut Machine, input: impl BufRead, mut out: impl Write) -> io::Result<()> {
+    machine.enable_trace();
+
+    let mut lines = input.lines();
+    loop {
+        write!(out, "> ")?;
+        out.flush()?;
+        let Some(line) = lines.next() else {
+            writeln!(out)?;
+            return Ok(());
+        };
+        let line = line?;
+
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let arg = match words.next().map(|w| w.parse::<usize>()).transpose() {
+            Ok(arg) => arg,
+            Err(e) => {
+                writeln!(out, "Invalid argument: {}", e)?;
+                continue;
+            }
+        };
+
+        match (command, arg) {
+            ("", _) => continue,
+            ("q" | "quit", None) => return Ok(()),
+            ("h" | "help", None) => write!(out, "{}", HELP)?,
+            ("p" | "print", None) => print_state(machine, &mut out)?,
+            ("s" | "step", n) => {
+                let n = n.unwrap_or(1);
+                match (0..n).try_for_each(|_| machine.step()) {
+                    Ok(()) => print_state(machine, &mut out)?,
+                    Err(exit) => print_exit(machine, exit, &mut out)?,
+                }
+            }
+            ("c" | "continue", None) => match machine.run() {
+                Stop::Breakpoint => {
+                    write!(out, "Breakpoint, ")?;
+                    print_state(machine, &mut out)?;
+                }
+                Stop::Exit(exit) => print_exit(machine, exit, &mut out)?,
+            },
+            ("b" | "break", Some(pc)) => {
+                machine.set_breakpoint(pc);
+                writeln!(out, "Breakpoint set at {}", pc)?;
+            }
+            ("d" | "delete", Some(pc)) => match machine.clear_breakpoint(pc) {
+                true => writeln!(out, "Breakpoint cleared at {}", pc)?,
+                false => writeln!(out, "No breakpoint at {}", pc)?,
+            },
+            ("l" | "list", n) => {
+                let n = n.unwrap_or(3) as i64;
+                let pc = machine.state.pc;
+                let end = machine.program.len() as i64;
+                for i in i64::max(0, pc - n)..i64::min(end, pc + n + 1) {
+                    writeln!(
+                        out,
+                        "{}{} {:4}: {}",
+                        if i == pc { '>' } else { ' ' },
+                        if machine.has_breakpoint(i as usize) {
+                            '*'
+                        } else {
+                            ' '
+                        },
+                        i,
+                        machine.table.disassemble_one(machine.program[i as usize])
+                    )?;
+                }
+            }
+            ("t" | "trace", n) => {
+                let trace = machine.trace();
+                let n = n.unwrap_or(10);
+                for entry in &trace[trace.len().saturating_sub(n)..] {
+                    writeln!(
+                        out,
+                        "{:4}: {:<10} acc: {}",
+                        entry.pc,
+                        machine.table.disassemble_one(entry.instruction),
+                        entry.state.acc
+                    )?;
+                }
+            }
+            ("x" | "dump", None) => write!(out, "{}", machine.table.disassemble(machine.program))?,
+            ("r" | "reset", None) => {
+                machine.reset();
+                print_state(machine, &mut out)?;
+            }
+            _ => writeln!(out, "Unknown command '{}', type 'help'", line.trim())?,
+        }
+    }
+}
+
+fn print_state(machine: &Machine, out: &mut impl Write) -> io::Result<()> {
+    let state = machine.state();
+    match machine.program.get(state.pc as usize) {
+        Some(next) if state.pc >= 0 => writeln!(
+            out,
+            "pc: {}, acc: {}, next: {}",
+            state.pc,
+            state.acc,
+            machine.table.disassemble_one(*next)
+        ),
+        _ => writeln!(out, "pc: {}, acc: {}", state.pc, state.acc),
+    }
+}
+
+fn print_exit(machine: &Machine, exit: Exit, out: &mut impl Write) -> io::Result<()> {
+    let state = machine.state();
+    match exit {
+        Exit::Terminated => write!(out, "Terminated, ")?,
+        Exit::Loop => write!(out, "Loop detected, ")?,
+        Exit::OutOfBounds => write!(out, "Jumped out of bounds, ")?,
+    }
+    writeln!(out, "pc: {}, acc: {}", state.pc, state.acc)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static EXAMPLE: &str = "\
+nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6
+";
+
+    #[test]
+    fn test_round_trip() {
+        let table = OpcodeTable::handheld();
+        let program = table.assemble(EXAMPLE).unwrap();
+        assert_eq!(program.len(), 9);
+        assert_eq!(program[4], Instruction { op: 1, arg: -3 });
+        assert_eq!(table.disassemble(&program), EXAMPLE);
+
+        let input = include_str!("day8.txt");
+        let program = table.assemble(input).unwrap();
+        assert_eq!(table.disassemble(&program).trim_end(), input.trim_end());
+        assert_eq!(
+            table.assemble(&table.disassemble(&program)).unwrap(),
+            program
+        );
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        let table = OpcodeTable::handheld();
+        assert_eq!(
+            table.assemble("nop +0\nmul +2\n"),
+            Err("Line 2: unknown opcode 'mul'".to_string())
+        );
+        assert_eq!(
+            table.assemble("acc\n"),
+            Err("Line 1: expected 'op arg', got 'acc'".to_string())
+        );
+        assert!(table
+            .assemble("jmp x\n")
+            .unwrap_err()
+            .starts_with("Line 1: invalid argument 'x'"));
+    }
+
+    #[test]
+    fn test_run() {
+        let table = OpcodeTable::handheld();
+        let mut program = table.assemble(EXAMPLE).unwrap();
+
+        let mut machine = Machine::new(&table, &program);
+        assert_eq!(machine.run(), Stop::Exit(Exit::Loop));
+        assert_eq!(machine.state(), State { pc: 1, acc: 5 });
+
+        program[7].op = table.lookup("nop").unwrap();
+        let mut machine = Machine::new(&table, &program);
+        assert_eq!(machine.run(), Stop::Exit(Exit::Terminated));
+        assert_eq!(machine.state(), State { pc: 9, acc: 8 });
+
+        let program = table.assemble("jmp -1\n").unwrap();
+        let mut machine = Machine::new(&table, &program);
+        assert_eq!(machine.run(), Stop::Exit(Exit::OutOfBounds));
+    }
+
+    #[test]
+    fn test_breakpoints_and_trace() {
+        let table = OpcodeTable::handheld();
+        let program = table.assemble(EXAMPLE).unwrap();
+        let mut machine = Machine::new(&table, &program);
+        machine.enable_trace();
+        assert!(machine.set_breakpoint(3));
+        assert!(!machine.set_breakpoint(3));
+
+        assert_eq!(machine.run(), Stop::Breakpoint);
+        assert_eq!(machine.state(), State { pc: 3, acc: 2 });
+        let pcs = machine.trace().iter().map(|t| t.pc).collect::<Vec<_>>();
+        assert_eq!(pcs, &[0, 1, 2, 6, 7]);
+
+        assert_eq!(machine.step(), Ok(()));
+        assert_eq!(machine.state(), State { pc: 4, acc: 5 });
+        assert!(machine.clear_breakpoint(3));
+        assert_eq!(machine.run(), Stop::Exit(Exit::Loop));
+        assert_eq!(machine.step(), Err(Exit::Loop));
+
+        machine.reset();
+        assert_eq!(machine.state(), State::default());
+        assert!(machine.trace().is_empty());
+    }
+
+    #[test]
+    fn test_successor() {
+        let table = OpcodeTable::handheld();
+        let program = table.assemble(EXAMPLE).unwrap();
+        let successors = (0..program.len())
+            .map(|pc| table.successor(program[pc], pc as i64))
+            .collect::<Vec<_>>();
+        assert_eq!(successors, &[1, 2, 6, 4, 1, 6, 7, 3, 9]);
+    }
+
+    #[test]
+    fn test_custom_opcode() {
+        let mut table = OpcodeTable::handheld();
+        let mul = table.register("mul", |s, arg| {
+            s.acc *= arg;
+            s.pc += 1;
+        });
+        assert_eq!(mul, 3);
+        assert_eq!(table.register("nop", |s, _| s.pc += 1), 2);
+
+        let program = table.assemble("acc +3\nmul +7\nacc -1\n").unwrap();
+        let mut machine = Machine::new(&table, &program);
+        assert_eq!(machine.run(), Stop::Exit(Exit::Terminated));
+        assert_eq!(machine.state().acc, 20);
+    }
+
+    #[test]
+    fn test_debugger() {
+        let table = OpcodeTable::handheld();
+        let program = table.assemble(EXAMPLE).unwrap();
+        let mut machine = Machine::new(&table, &program);
+
+        let input = "s 2\nb 6\nc\nl 1\nt 2\nd 6\nd 6\nc\nfoo\nb x\nq\n";
+        let mut out = Vec::new();
+        debug(&mut machine, input.as_bytes(), &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(
+            out.split("> ").collect::<Vec<_>>(),
+            &[
+                "",
+                "pc: 2, acc: 1, next: jmp +4\n",
+                "Breakpoint set at 6\n",
+                "Breakpoint, pc: 6, acc: 1, next: acc +1\n",
+                "      5: acc -99\n>*    6: acc +1\n      7: jmp -4\n",
+                "   1: acc +1     acc: 1\n   2: jmp +4     acc: 1\n",
+                "Breakpoint cleared at 6\n",
+                "No breakpoint at 6\n",
+                "Loop detected, pc: 1, acc: 5\n",
+                "Unknown command 'foo', type 'help'\n",
+                "Invalid argument: invalid digit found in string\n",
+                "",
+            ]
+        );
+    }
+}
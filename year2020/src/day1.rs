@@ -48,29 +48,25 @@
 //! In your expense report, **what is the product of the three entries that sum to `2020`**?
 //!
 
-use itertools::iproduct;
+use crate::ksum::k_sum;
 
-fn load() -> Vec<u16> {
+fn load() -> Vec<i64> {
     include_str!("day1.txt")
         .lines()
-        .map(|s| s.parse::<u16>().unwrap())
+        .map(|s| s.parse::<i64>().unwrap())
         .collect::<Vec<_>>()
 }
 
+fn product_of_sum(k: usize) -> i64 {
+    k_sum(&load(), k, 2020).unwrap().values.iter().product()
+}
+
 pub fn part1() -> i64 {
-    let numbers = load();
-    iproduct!(numbers.iter(), numbers.iter())
-        .find(|(&i, &j)| i + j == 2020)
-        .map(|(&i, &j)| i as i64 * j as i64)
-        .unwrap()
+    product_of_sum(2)
 }
 
 pub fn part2() -> i64 {
-    let numbers = load();
-    iproduct!(numbers.iter(), numbers.iter(), numbers.iter())
-        .find(|(&i, &j, &l)| i + j + l == 2020)
-        .map(|(&i, &j, &l)| i as i64 * j as i64 * l as i64)
-        .unwrap()
+    product_of_sum(3)
 }
 
 #[test]
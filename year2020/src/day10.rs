@@ -49,30 +49,30 @@
 //! in order to use every adapter, you'd need to choose them like this:
 //!
 //! * The charging outlet has an effective rating of `0` jolts, so the only adapters that could
-//! connect to it directly would need to have a joltage rating of `1`, `2`, or `3` jolts.
-//! Of these, only one you have is an adapter rated `1` jolt (difference of **`1`**).
+//!   connect to it directly would need to have a joltage rating of `1`, `2`, or `3` jolts.
+//!   Of these, only one you have is an adapter rated `1` jolt (difference of **`1`**).
 //!
 //! * From your `1`-jolt rated adapter, the only choice is your `4`-jolt rated adapter
-//! (difference of **`3`**).
+//!   (difference of **`3`**).
 //!
 //! * From the 4-jolt rated adapter, the adapters rated `5`, `6`, or `7` are valid choices.
-//! However, in order to not skip any adapters, you have to pick the adapter rated `5` jolts
-//! (difference of **`1`**).
+//!   However, in order to not skip any adapters, you have to pick the adapter rated `5` jolts
+//!   (difference of **`1`**).
 //!
 //! * Similarly, the next choices would need to be the adapter rated `6` and then the adapter
-//! rated `7` (with difference of `1` and **`1`**).
+//!   rated `7` (with difference of `1` and **`1`**).
 //!
 //! * The only adapter that works with the `7`-jolt rated adapter is the one rated `10` jolts
-//! (difference of **`3`**).
+//!   (difference of **`3`**).
 //!
 //! * From `10`, the choices are `11` or `12`; choose `11` (difference of **`1`**) and then `12`
-//! (difference of **`1`**).
+//!   (difference of **`1`**).
 //!
 //! * After `12`, only valid adapter has a rating of `15` (difference of **`3`**),
-//! then `16` (difference of **`1`**), then `19` (difference of **`3`**).
+//!   then `16` (difference of **`1`**), then `19` (difference of **`3`**).
 //!
 //! * Finally, your device's built-in adapter is always `3` higher than the highest adapter,
-//! so its rating is `22` jolts (always a difference of **`3`**).
+//!   so its rating is `22` jolts (always a difference of **`3`**).
 //!
 //! In this example, when using every adapter, there are **`7`** differences of `1` jolt
 //! and **`5`** differences of `3` jolts.
@@ -203,23 +203,36 @@ pub fn load() -> Vec<i64> {
     numbers
 }
 
-/// Count the number of valid permutations in a sorted subsequence of adapters where each adapter
-/// always is rated 1-jolt lower than the previous adapter in the sequence.
-///
-/// A valid permutation can exclude adapters as long as the difference in rating between two
-/// consecutive adapters is between 1-3 jolts. The first and last adapters can not be excluded.
+/// The largest difference in joltage between two chained adapters.
+const MAX_STEP: i64 = 3;
+
+/// Count the ways to chain a sorted list of adapters from the first to the last, where each
+/// adapter is rated between 1 and `max_step` jolts higher than the previous one.
 ///
-fn count_subsequence_permutations(sequence: &[i64]) -> i64 {
-    debug_assert!(sequence.windows(2).all(|w| w[1] - w[0] == 1));
+/// `ways[i]` is the number of chains ending in adapter `i`, the sum of `ways[j]` over the earlier
+/// adapters it can connect to. A gap larger than `max_step` leaves no way to reach the end.
+/// Returns `None` if the count does not fit in a `u128`.
+fn count_arrangements(adapters: &[i64], max_step: i64) -> Option<u128> {
+    if adapters.is_empty() {
+        return Some(0);
+    }
 
-    match sequence.len() {
-        1 => 1,
-        2 => 1,
-        3 => 2,
-        4 => 4,
-        5 => 7,
-        len => panic!("Unexpected length {} {:?}", len, sequence),
+    let mut ways = vec![0u128; adapters.len()];
+    ways[0] = 1;
+    for i in 1..adapters.len() {
+        let mut sum = 0u128;
+        for j in (0..i).rev() {
+            let step = adapters[i] - adapters[j];
+            if step > max_step {
+                break;
+            }
+            if step > 0 {
+                sum = sum.checked_add(ways[j])?;
+            }
+        }
+        ways[i] = sum;
     }
+    ways.last().copied()
 }
 
 pub fn part1() -> i64 {
@@ -235,33 +248,25 @@ pub fn part1() -> i64 {
 }
 
 pub fn part2() -> i64 {
-    // The key insight is that the adapters when sorted always has a 1 or 3 jolt difference
-    // in rating. A valid permutation must include both adapters if there is a 3 jolts
-    // difference between them, otherwise the difference between adapters becomes larger than 3.
-    // This allows us to split the full sequence into subsequences with a 1 jolt difference
-    // between each adapter and then count the permutations for each subsequence individually
-    // and multiply them together.
-
-    let mut n = 0;
-    let mut total_permutations = 1;
+    let count = count_arrangements(&load(), MAX_STEP).unwrap();
+    i64::try_from(count).unwrap()
+}
 
-    // loop and calculate delta until we find a 3 jolt difference.
-    let data = load();
-    for i in 1..data.len() {
-        match data[i] - data[i - 1] {
-            1 => continue,
-            3 => {
-                // Calculate number of permutations from the start of current subsequence
-                // up until the 3 jolt difference.
-                total_permutations *= count_subsequence_permutations(&data[n..i]);
+/// Count the valid permutations of a run of adapters that each are rated 1 jolt higher than the
+/// previous one, keeping the first and last adapter. Used as an oracle for the run lengths found
+/// in the puzzle input.
+#[cfg(test)]
+fn count_subsequence_permutations(sequence: &[i64]) -> i64 {
+    debug_assert!(sequence.windows(2).all(|w| w[1] - w[0] == 1));
 
-                // Start a new subsequence after the 3 jolt difference.
-                n = i;
-            }
-            delta => panic!("Unexpected delta {}", delta),
-        }
+    match sequence.len() {
+        1 => 1,
+        2 => 1,
+        3 => 2,
+        4 => 4,
+        5 => 7,
+        len => panic!("Unexpected length {} {:?}", len, sequence),
     }
-    total_permutations
 }
 
 #[test]
@@ -287,3 +292,57 @@ fn test_count_subsequence_permutations() {
     // [1, 2, 3, 4, 5], [1, 2, 3, 5], [1, 2, 5], [1, 2, 4, 5], [1, 4, 5], [1, 3, 4, 5], [1, 3, 5]
     assert_eq!(count_subsequence_permutations(&[1, 2, 3, 4, 5]), 7);
 }
+
+#[test]
+fn test_count_arrangements() {
+    let small = [16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
+    let mut adapters = small.to_vec();
+    adapters.extend([0, 22]);
+    adapters.sort();
+    assert_eq!(count_arrangements(&adapters, 3), Some(8));
+
+    // Runs of 1 jolt steps match the lookup table.
+    for len in 1..=5 {
+        let run = (1..=len).collect::<Vec<i64>>();
+        assert_eq!(
+            count_arrangements(&run, 3),
+            Some(count_subsequence_permutations(&run) as u128)
+        );
+    }
+
+    // Gaps other than 1 and 3, and a gap too large to bridge.
+    assert_eq!(count_arrangements(&[0, 2, 4, 5], 3), Some(2));
+    assert_eq!(count_arrangements(&[0, 2, 6], 3), Some(0));
+    assert_eq!(count_arrangements(&[0, 2, 6], 4), Some(1));
+    assert_eq!(count_arrangements(&[0], 3), Some(1));
+    assert_eq!(count_arrangements(&[], 3), Some(0));
+
+    // Adapters with the same rating can not be chained.
+    assert_eq!(count_arrangements(&[0, 1, 1, 2], 3), Some(3));
+}
+
+#[test]
+fn test_count_arrangements_long_runs() {
+    // A run of 1 jolt steps with steps up to 2 counts the Fibonacci numbers.
+    let run = (0..100).collect::<Vec<i64>>();
+    assert_eq!(count_arrangements(&run, 2), Some(354224848179261915075));
+    assert!(count_arrangements(&run, 3).unwrap() > i64::MAX as u128);
+
+    let run = (0..200).collect::<Vec<i64>>();
+    assert_eq!(count_arrangements(&run, 3), None);
+}
+
+#[test]
+fn test_part2_oracle() {
+    // Split the input into runs of 1 jolt steps at the 3 jolt gaps and multiply the table counts.
+    let data = load();
+    let mut n = 0;
+    let mut total = 1;
+    for i in 1..data.len() {
+        if data[i] - data[i - 1] == 3 {
+            total *= count_subsequence_permutations(&data[n..i]);
+            n = i;
+        }
+    }
+    assert_eq!(part2(), total);
+}
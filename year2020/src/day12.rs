@@ -1,4 +1,6 @@
-#[derive(Debug, Clone, Copy)]
+//! # Day 12: Rain Risk
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Op {
     North,
     East,
@@ -23,6 +25,16 @@ struct Ship {
     d: Direction,
 }
 
+/// A ship steered by a waypoint relative to its position, moved by `N`, `E`, `S`, `W` and rotated
+/// around the ship by `L` and `R`. Positions grow east and south, like [`Ship`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WaypointShip {
+    x: i64,
+    y: i64,
+    wx: i64,
+    wy: i64,
+}
+
 impl Op {
     fn parse(s: &str) -> Result<Op, String> {
         match s {
@@ -91,14 +103,94 @@ impl Ship {
     }
 }
 
-fn load() -> Vec<(Op, i16)> {
-    include_str!("day12.txt")
+impl WaypointShip {
+    fn new() -> WaypointShip {
+        WaypointShip {
+            x: 0,
+            y: 0,
+            wx: 10,
+            wy: -1,
+        }
+    }
+
+    fn next(self, op: Op, num: i16) -> WaypointShip {
+        let num = num as i64;
+        match op {
+            Op::North => WaypointShip {
+                wy: self.wy - num,
+                ..self
+            },
+            Op::East => WaypointShip {
+                wx: self.wx + num,
+                ..self
+            },
+            Op::South => WaypointShip {
+                wy: self.wy + num,
+                ..self
+            },
+            Op::West => WaypointShip {
+                wx: self.wx - num,
+                ..self
+            },
+            Op::Left => self.rotate(-num),
+            Op::Right => self.rotate(num),
+            Op::Forward => WaypointShip {
+                x: self.x + self.wx * num,
+                y: self.y + self.wy * num,
+                ..self
+            },
+        }
+    }
+
+    /// Rotate the waypoint clockwise around the ship by a multiple of 90 degrees.
+    fn rotate(self, degrees: i64) -> WaypointShip {
+        assert_eq!(degrees % 90, 0);
+        let (wx, wy) = match (degrees / 90).rem_euclid(4) {
+            0 => (self.wx, self.wy),
+            1 => (-self.wy, self.wx),
+            2 => (-self.wx, -self.wy),
+            3 => (self.wy, -self.wx),
+            _ => unreachable!(),
+        };
+        WaypointShip { wx, wy, ..self }
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<(Op, i16)>, String> {
+    input
         .lines()
-        .map(|s| {
-            let (op_str, num_str) = s.split_at(1);
-            (Op::parse(op_str).unwrap(), num_str.parse::<i16>().unwrap())
+        .enumerate()
+        .map(|(i, s)| {
+            let (op_str, num_str) = s
+                .split_at_checked(1)
+                .ok_or(format!("Line {}: empty", i + 1))?;
+            let op = Op::parse(op_str).map_err(|e| format!("Line {}: {}", i + 1, e))?;
+            let num = num_str
+                .parse::<i16>()
+                .map_err(|e| format!("Line {}: {}", i + 1, e))?;
+            if matches!(op, Op::Left | Op::Right) && num % 90 != 0 {
+                return Err(format!(
+                    "Line {}: rotation {} is not a multiple of 90",
+                    i + 1,
+                    num
+                ));
+            }
+            Ok((op, num))
         })
-        .collect::<Vec<_>>()
+        .collect()
+}
+
+fn load() -> Vec<(Op, i16)> {
+    parse(include_str!("day12.txt")).unwrap()
+}
+
+/// The waypoint ship after each instruction, starting with its initial state.
+fn trace(moves: &[(Op, i16)]) -> Vec<WaypointShip> {
+    let mut trace = vec![WaypointShip::new()];
+    for &(op, num) in moves {
+        trace.push(trace[trace.len() - 1].next(op, num));
+    }
+    trace
 }
 
 pub fn part1() -> i64 {
@@ -111,7 +203,95 @@ pub fn part1() -> i64 {
     ship.x.abs() as i64 + ship.y.abs() as i64
 }
 
+pub fn part2() -> i64 {
+    let ship = *trace(&load()).last().unwrap();
+    ship.x.abs() + ship.y.abs()
+}
+
+/// Print the ship and waypoint positions after each instruction of the puzzle input or `FILE`.
+pub fn report(args: &[String]) -> Result<(), String> {
+    let moves = match args {
+        [] => load(),
+        [file] => parse(&std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?)?,
+        _ => return Err("Usage: ferry [FILE]".to_string()),
+    };
+
+    let trace = trace(&moves);
+    println!("{:<10}  {:>14}  {:>14}", "", "ship", "waypoint");
+    for (i, ship) in trace.iter().enumerate() {
+        let instruction = match i {
+            0 => String::new(),
+            _ => format!("{:?} {}", moves[i - 1].0, moves[i - 1].1),
+        };
+        println!(
+            "{:<10}  {:>14}  {:>14}",
+            instruction,
+            format!("{},{}", ship.x, ship.y),
+            format!("{},{}", ship.wx, ship.wy)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+static EXAMPLE: &str = "F10
+N3
+F7
+R90
+F11
+";
+
 #[test]
 fn test_part1() {
     assert_eq!(part1(), 508)
 }
+
+#[test]
+fn test_part2() {
+    assert_eq!(part2(), 30761);
+}
+
+#[test]
+fn test_trace() {
+    let trace = trace(&parse(EXAMPLE).unwrap());
+    let positions = trace
+        .iter()
+        .map(|s| ((s.x, s.y), (s.wx, s.wy)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        positions,
+        [
+            ((0, 0), (10, -1)),
+            ((100, -10), (10, -1)),
+            ((100, -10), (10, -4)),
+            ((170, -38), (10, -4)),
+            ((170, -38), (4, 10)),
+            ((214, 72), (4, 10)),
+        ]
+    );
+}
+
+#[test]
+fn test_rotate() {
+    let ship = WaypointShip::new();
+    assert_eq!(ship.rotate(0), ship);
+    assert_eq!(ship.rotate(360), ship);
+    assert_eq!(ship.rotate(-720), ship);
+    assert_eq!(ship.rotate(90), ship.rotate(-270));
+    assert_eq!(ship.rotate(180), ship.rotate(-180));
+    assert_eq!(ship.rotate(450), ship.rotate(90));
+    assert_eq!((ship.rotate(90).wx, ship.rotate(90).wy), (1, 10));
+    assert_eq!((ship.rotate(-90).wx, ship.rotate(-90).wy), (-1, -10));
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        parse("F10\nR45\n").unwrap_err(),
+        "Line 2: rotation 45 is not a multiple of 90"
+    );
+    assert_eq!(parse("X1\n").unwrap_err(), "Line 1: unexpected op X");
+    assert!(parse("F\n").unwrap_err().starts_with("Line 1: "));
+    assert_eq!(parse("\n").unwrap_err(), "Line 1: empty");
+    assert_eq!(parse("L270\nR540\n").unwrap().len(), 2);
+}
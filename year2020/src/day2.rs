@@ -51,50 +51,347 @@
 //! **How many passwords are valid** according to the new interpretation of the policies?
 //!
 
-struct Line(usize, usize, char, &'static str);
-
-impl Line {
-    fn scan(line: &'static str) -> Line {
-        let parts = line
-            .split(&['-', ' ', ':'][..])
-            .filter(|s| s.len() > 0)
-            .collect::<Vec<_>>();
-
-        assert_eq!(parts.len(), 4);
-        let min = parts[0].parse::<usize>().unwrap();
-        let max = parts[1].parse::<usize>().unwrap();
-        assert!(min <= max);
-
-        assert_eq!(parts[2].len(), 1);
-        let char = parts[2].chars().next().unwrap();
-        let password = parts[3];
-        Line(min, max, char, password)
+use std::fmt;
+
+/// How a bare `A-B C` rule is read: as a count of `C` between `A` and `B`, or as two positions
+/// of which exactly one holds `C`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reading {
+    Count,
+    Positions,
+}
+
+/// The characters a rule looks for. The named classes only cover ASCII, like the passwords.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Char(char),
+    Digit,
+    Lower,
+    Upper,
+}
+
+/// A password policy, parsed from comma separated rules that must all hold.
+///
+/// - `A-B C` is read according to the [`Reading`],
+/// - `count A-B C` requires between `A` and `B` characters of `C`, either bound may be left out,
+/// - `xor A-B C` requires exactly one of positions `A` and `B` (1-based) to hold `C`,
+///
+/// where `C` is a single character or one of the classes `digit`, `lower` and `upper`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Policy {
+    Count {
+        class: Class,
+        min: usize,
+        max: Option<usize>,
+    },
+    Xor {
+        class: Class,
+        first: usize,
+        second: usize,
+    },
+    All(Vec<Policy>),
+}
+
+struct Line<'a> {
+    policy: Policy,
+    password: &'a str,
+}
+
+impl Class {
+    fn parse(s: &str) -> Result<Class, String> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), s) {
+            (Some(c), None, _) => Ok(Class::Char(c)),
+            (_, _, "digit") => Ok(Class::Digit),
+            (_, _, "lower") => Ok(Class::Lower),
+            (_, _, "upper") => Ok(Class::Upper),
+            _ => Err(format!("Unknown character class '{}'", s)),
+        }
+    }
+
+    fn matches(self, c: char) -> bool {
+        match self {
+            Class::Char(x) => c == x,
+            Class::Digit => c.is_ascii_digit(),
+            Class::Lower => c.is_ascii_lowercase(),
+            Class::Upper => c.is_ascii_uppercase(),
+        }
+    }
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Class::Char(c) => write!(f, "'{}'", c),
+            Class::Digit => write!(f, "digit"),
+            Class::Lower => write!(f, "lower"),
+            Class::Upper => write!(f, "upper"),
+        }
+    }
+}
+
+impl Policy {
+    fn parse(s: &str, reading: Reading) -> Result<Policy, String> {
+        let mut rules = s
+            .split(',')
+            .map(|rule| Policy::parse_rule(rule.trim(), reading))
+            .collect::<Result<Vec<_>, _>>()?;
+        match rules.len() {
+            1 => Ok(rules.remove(0)),
+            _ => Ok(Policy::All(rules)),
+        }
+    }
+
+    fn parse_rule(s: &str, reading: Reading) -> Result<Policy, String> {
+        let parts = s.split_whitespace().collect::<Vec<_>>();
+        let (reading, range, class) = match parts[..] {
+            [range, class] => (reading, range, class),
+            ["count", range, class] => (Reading::Count, range, class),
+            ["xor", range, class] => (Reading::Positions, range, class),
+            _ => return Err(format!("Invalid rule '{}'", s)),
+        };
+
+        let class = Class::parse(class)?;
+        let (a, b) = range
+            .split_once('-')
+            .ok_or(format!("Invalid range '{}'", range))?;
+        let bound = |n: &str| match n {
+            "" => Ok(None),
+            n => n
+                .parse::<usize>()
+                .map(Some)
+                .map_err(|e| format!("Invalid range '{}': {}", range, e)),
+        };
+        let (a, b) = (bound(a)?, bound(b)?);
+
+        match reading {
+            Reading::Count => {
+                let min = a.unwrap_or(0);
+                if b.is_some_and(|max| max < min) {
+                    return Err(format!("Invalid range '{}': empty", range));
+                }
+                Ok(Policy::Count { class, min, max: b })
+            }
+            Reading::Positions => match (a, b) {
+                (Some(first), Some(second)) if first > 0 && second > 0 => Ok(Policy::Xor {
+                    class,
+                    first,
+                    second,
+                }),
+                _ => Err(format!("Invalid positions '{}'", range)),
+            },
+        }
+    }
+
+    /// The reasons `password` fails this policy, empty if it is valid.
+    fn violations(&self, password: &str) -> Vec<String> {
+        match self {
+            Policy::Count { class, min, max } => {
+                let count = password.chars().filter(|&c| class.matches(c)).count();
+                let range = match max {
+                    Some(max) => format!("{}-{}", min, max),
+                    None => format!("at least {}", min),
+                };
+                match count >= *min && max.is_none_or(|max| count <= max) {
+                    true => vec![],
+                    false => vec![format!("expected {} of {}, found {}", range, class, count)],
+                }
+            }
+            Policy::Xor {
+                class,
+                first,
+                second,
+            } => {
+                let at = |p: usize| {
+                    password
+                        .chars()
+                        .nth(p - 1)
+                        .is_some_and(|c| class.matches(c))
+                };
+                match (at(*first), at(*second)) {
+                    (true, false) | (false, true) => vec![],
+                    (true, true) => vec![format!(
+                        "both positions {} and {} hold {}",
+                        first, second, class
+                    )],
+                    (false, false) => vec![format!(
+                        "neither position {} nor {} holds {}",
+                        first, second, class
+                    )],
+                }
+            }
+            Policy::All(rules) => rules.iter().flat_map(|r| r.violations(password)).collect(),
+        }
+    }
+
+    fn is_valid(&self, password: &str) -> bool {
+        self.violations(password).is_empty()
+    }
+}
+
+impl Line<'_> {
+    fn scan(line: &str, reading: Reading) -> Result<Line<'_>, String> {
+        let (policy, password) = line
+            .split_once(':')
+            .ok_or(format!("Missing password in '{}'", line))?;
+        Ok(Line {
+            policy: Policy::parse(policy, reading)?,
+            password: password.trim(),
+        })
     }
 }
 
-fn init() -> Vec<Line> {
-    include_str!("day2.txt")
+fn parse(input: &str, reading: Reading) -> Result<Vec<Line<'_>>, String> {
+    input
         .lines()
-        .map(|s| Line::scan(s))
-        .collect::<Vec<_>>()
+        .enumerate()
+        .map(|(i, line)| Line::scan(line, reading).map_err(|e| format!("Line {}: {}", i + 1, e)))
+        .collect()
 }
 
-pub fn part1() -> i64 {
-    init()
+fn count_valid(reading: Reading) -> i64 {
+    parse(include_str!("day2.txt"), reading)
+        .unwrap()
         .iter()
-        .filter(|line| (line.0..=line.1).contains(&line.3.chars().filter(|c| *c == line.2).count()))
+        .filter(|line| line.policy.is_valid(line.password))
         .count() as i64
 }
 
+pub fn part1() -> i64 {
+    count_valid(Reading::Count)
+}
+
 pub fn part2() -> i64 {
-    init()
-        .iter()
-        .filter(|line| {
-            let a = line.3.chars().nth(line.0 - 1).unwrap();
-            let b = line.3.chars().nth(line.1 - 1).unwrap();
-            (a == line.2) ^ (b == line.2)
-        })
-        .count() as i64
+    count_valid(Reading::Positions)
+}
+
+/// List the passwords of the puzzle input or `FILE` that fail their policy, and why. Bare rules
+/// are read as counts, or as positions with `--positions`.
+pub fn report(args: &[String]) -> Result<(), String> {
+    let (reading, args) = match args {
+        [flag, rest @ ..] if flag == "--positions" => (Reading::Positions, rest),
+        _ => (Reading::Count, args),
+    };
+    let input = match args {
+        [] => include_str!("day2.txt").to_string(),
+        [file] => std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?,
+        _ => return Err("Usage: passwords [--positions] [FILE]".to_string()),
+    };
+
+    let lines = parse(&input, reading)?;
+    let mut invalid = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let violations = line.policy.violations(line.password);
+        if !violations.is_empty() {
+            invalid += 1;
+            println!("{:>5} {}: {}", i + 1, line.password, violations.join("; "));
+        }
+    }
+    println!("{} of {} passwords invalid", invalid, lines.len());
+    Ok(())
+}
+
+#[cfg(test)]
+static EXAMPLE: &str = "1-3 a: abcde
+1-3 b: cdefg
+2-9 c: ccccccccc
+";
+
+#[test]
+fn test_example() {
+    let valid = |reading| {
+        parse(EXAMPLE, reading)
+            .unwrap()
+            .iter()
+            .map(|line| line.policy.is_valid(line.password))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(valid(Reading::Count), [true, false, true]);
+    assert_eq!(valid(Reading::Positions), [true, false, false]);
+}
+
+#[test]
+fn test_ascii_classes() {
+    let classes = [Class::Digit, Class::Lower, Class::Upper];
+    let matches = |c| classes.map(|class| class.matches(c));
+    assert_eq!(matches('7'), [true, false, false]);
+    assert_eq!(matches('q'), [false, true, false]);
+    assert_eq!(matches('Q'), [false, false, true]);
+    for c in ['٣', 'é', 'É'] {
+        assert_eq!(matches(c), [false; 3], "{}", c);
+    }
+    assert!(Class::Char('é').matches('é'));
+}
+
+#[test]
+fn test_violations() {
+    let violations = |policy: &str, password: &str| {
+        Policy::parse(policy, Reading::Count)
+            .unwrap()
+            .violations(password)
+    };
+    assert_eq!(
+        violations("1-3 b", "cdefg"),
+        ["expected 1-3 of 'b', found 0"]
+    );
+    assert_eq!(
+        violations("xor 2-9 c", "ccccccccc"),
+        ["both positions 2 and 9 hold 'c'"]
+    );
+    assert_eq!(
+        violations("xor 1-3 b", "cdefg"),
+        ["neither position 1 nor 3 holds 'b'"]
+    );
+    assert_eq!(
+        violations("xor 1-12 b", "bcd"),
+        Vec::<String>::new(),
+        "positions past the end hold nothing"
+    );
+
+    // Open ranges and character classes.
+    assert!(violations("2- digit", "a1b2").is_empty());
+    assert_eq!(
+        violations("2- digit", "a1b"),
+        ["expected at least 2 of digit, found 1"]
+    );
+    assert!(violations("-0 upper", "abc").is_empty());
+    assert_eq!(
+        violations("-0 upper", "aBc"),
+        ["expected 0-0 of upper, found 1"]
+    );
+
+    // Composed rules report every failure.
+    assert!(violations("8- lower, 1- digit, xor 1-2 upper", "Abcdefghi1").is_empty());
+    assert_eq!(
+        violations("8- lower, 1- digit, xor 1-2 upper", "ABcd"),
+        [
+            "expected at least 8 of lower, found 2",
+            "expected at least 1 of digit, found 0",
+            "both positions 1 and 2 hold upper",
+        ]
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = |input| parse(input, Reading::Positions).err().unwrap();
+    assert_eq!(
+        error("1-3 a abcde"),
+        "Line 1: Missing password in '1-3 a abcde'"
+    );
+    assert_eq!(
+        error("1-3 a: a\n1-3 ab: a"),
+        "Line 2: Unknown character class 'ab'"
+    );
+    assert_eq!(error("1-3: a"), "Line 1: Invalid rule '1-3'");
+    assert_eq!(error("13 a: a"), "Line 1: Invalid range '13'");
+    assert_eq!(error("0-3 a: a"), "Line 1: Invalid positions '0-3'");
+    assert_eq!(error("2- a: a"), "Line 1: Invalid positions '2-'");
+    assert_eq!(
+        error("count 3-1 a: a"),
+        "Line 1: Invalid range '3-1': empty"
+    );
+    assert!(error("x-1 a: a").starts_with("Line 1: Invalid range 'x-1': "));
 }
 
 #[test]
@@ -88,19 +88,142 @@
 //! **What do you get if you multiply together the number of trees encountered on each of the listed
 //! slopes?**
 
+use std::fmt;
+
+use advent_of_code_core::visual::{self, Color, Frame};
 use bit_vec::BitVec;
 
+/// Whether the map repeats only to the right and left, or also above and below.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Wrap {
+    Horizontal,
+    Both,
+}
+
+/// Each step moves `right / per` squares right, rounded down, and `down` squares down. Negative
+/// values move left and up, so `1/2,1` moves right every second row and `-1,-2` up and left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Slope {
+    right: i64,
+    per: i64,
+    down: i64,
+}
+
+/// The squares visited and trees hit on a slope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stats {
+    slope: Slope,
+    steps: usize,
+    trees: usize,
+}
+
 struct Map {
     width: usize,
     height: usize,
     data: BitVec,
 }
 
+impl Slope {
+    /// The largest divisor, which bounds a vertically wrapping path to `MAX_PER * width * height`
+    /// steps.
+    const MAX_PER: i64 = 1000;
+
+    /// The largest `MAX` the slopes command enumerates, about 4600 slopes.
+    const MAX_ENUMERATED: i64 = 10;
+
+    const fn new(right: i64, down: i64) -> Slope {
+        Slope {
+            right,
+            per: 1,
+            down,
+        }
+    }
+
+    /// Parse `RIGHT,DOWN` or `RIGHT/PER,DOWN`.
+    fn parse(s: &str) -> Result<Slope, String> {
+        let number = |n: &str| {
+            n.trim()
+                .parse::<i64>()
+                .map_err(|e| format!("Invalid slope '{}': {}", s, e))
+        };
+        let (right, down) = s
+            .split_once(',')
+            .ok_or(format!("Invalid slope '{}', expected RIGHT,DOWN", s))?;
+        let (right, per) = match right.split_once('/') {
+            Some((right, per)) => (number(right)?, number(per)?),
+            None => (number(right)?, 1),
+        };
+        if !(1..=Slope::MAX_PER).contains(&per) {
+            return Err(format!(
+                "Invalid slope '{}': divisor must be from 1 to {}",
+                s,
+                Slope::MAX_PER
+            ));
+        }
+        Ok(Slope {
+            right,
+            per,
+            down: number(down)?,
+        })
+    }
+
+    /// Every slope with numerator, divisor and `down` within `max`, the fractions reduced to
+    /// their lowest terms so no path is repeated, except `0,0` which never moves.
+    fn all(max: i64) -> impl Iterator<Item = Slope> {
+        (-max..=max).flat_map(move |down| {
+            (-max..=max).flat_map(move |right| {
+                (1..=max)
+                    .filter(move |&per| gcd(right, per) == 1 && (right, down) != (0, 0))
+                    .map(move |per| Slope { right, per, down })
+            })
+        })
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    match b {
+        0 => a.abs(),
+        _ => gcd(b, a % b),
+    }
+}
+
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.per {
+            1 => write!(f, "{},{}", self.right, self.down),
+            per => write!(f, "{}/{},{}", self.right, per, self.down),
+        }
+    }
+}
+
+impl Stats {
+    /// The share of visited squares holding a tree.
+    fn hit_rate(&self) -> f64 {
+        match self.steps {
+            0 => 0.0,
+            steps => self.trees as f64 / steps as f64,
+        }
+    }
+}
+
+/// The slope hitting the fewest trees, the first one on a tie. Slopes that leave the map on the
+/// first step are skipped.
+fn safest(stats: impl IntoIterator<Item = Stats>) -> Option<Stats> {
+    stats
+        .into_iter()
+        .filter(|stats| stats.steps > 0)
+        .min_by_key(|stats| stats.trees)
+}
+
 impl Map {
     fn load() -> Map {
+        Map::parse(include_str!("day3.txt"))
+    }
+
+    fn parse(input: &str) -> Map {
         let mut width: Option<usize> = None;
 
-        let data = include_str!("day3.txt")
+        let data = input
             .lines()
             .flat_map(|l| {
                 assert_eq!(*width.get_or_insert(l.len()), l.len());
@@ -120,21 +243,95 @@ impl Map {
         }
     }
 
-    fn test_slope(&self, step_x: usize, step_y: usize) -> i64 {
-        let mut x = 0;
-        let mut y = 0;
-        let mut num = 0;
-        loop {
-            x += step_x;
-            y += step_y;
+    /// Whether there is a tree at `(x, y)`, wrapping around the repeating map.
+    fn tree(&self, x: i64, y: i64) -> bool {
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.rem_euclid(self.height as i64) as usize;
+        self.data[y * self.width + x]
+    }
+
+    /// The squares visited on `slope`, not counting the start, as positions on the map. Without
+    /// vertical wrapping the path ends at the bottom of the map, so the slope has to move down.
+    /// With it, the path ends when it is back at the start.
+    fn path(&self, slope: Slope, wrap: Wrap) -> Result<impl Iterator<Item = (i64, i64)>, String> {
+        if wrap == Wrap::Horizontal && slope.down <= 0 {
+            return Err(format!("Slope {} never reaches the bottom", slope));
+        }
+
+        // Each step moves `whole` squares and `part / per` of a square right. The fractions are
+        // carried over in `fraction`, so positions never need the step count.
+        let (width, height) = (self.width as i64, self.height as i64);
+        let whole = slope.right.div_euclid(slope.per).rem_euclid(width);
+        let part = slope.right.rem_euclid(slope.per);
+        let (mut x, mut y, mut fraction) = (0, 0i64, 0);
+        let mut done = false;
+
+        Ok(std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            fraction += part;
+            x = (x + whole + fraction / slope.per) % width;
+            fraction %= slope.per;
+            y = match wrap {
+                Wrap::Horizontal => match y.checked_add(slope.down) {
+                    Some(y) if y < height => y,
+                    _ => {
+                        done = true;
+                        return None;
+                    }
+                },
+                Wrap::Both => (y + slope.down.rem_euclid(height)) % height,
+            };
+            done = wrap == Wrap::Both && (x, y, fraction) == (0, 0, 0);
+            Some((x, y))
+        }))
+    }
+
+    /// Count the squares visited and trees hit while walking the path, drawing each step.
+    fn stats(&self, slope: Slope, wrap: Wrap) -> Result<Stats, String> {
+        let mut frame = visual::enabled().then(|| self.draw());
+        let mut stats = Stats {
+            slope,
+            steps: 0,
+            trees: 0,
+        };
+        for (x, y) in self.path(slope, wrap)? {
+            stats.steps += 1;
+            stats.trees += self.tree(x, y) as usize;
+            if let Some(frame) = frame.as_mut() {
+                self.mark(frame, x, y);
+                visual::frame(|| frame.clone());
+            }
+        }
+        Ok(stats)
+    }
+
+    fn test_slope(&self, right: i64, down: i64) -> i64 {
+        self.stats(Slope::new(right, down), Wrap::Horizontal)
+            .unwrap()
+            .trees as i64
+    }
 
-            if y >= self.height {
-                break;
+    /// Draw the map like the puzzle text, with trees marked `#`.
+    fn draw(&self) -> Frame {
+        let mut frame = Frame::new(self.width, self.height);
+        for ty in 0..self.height as i64 {
+            for tx in 0..self.width as i64 {
+                if self.tree(tx, ty) {
+                    frame.mark(tx, ty, '#', Color::Green);
+                }
             }
+        }
+        frame
+    }
 
-            num += self.data[y * self.width + x % self.width] as i64
+    /// Mark a square of the path, `X` for a tree hit and `O` for an open square passed.
+    fn mark(&self, frame: &mut Frame, x: i64, y: i64) {
+        match self.tree(x, y) {
+            true => frame.mark(x, y, 'X', Color::Red),
+            false => frame.mark(x, y, 'O', Color::Yellow),
         }
-        num
     }
 }
 
@@ -151,6 +348,78 @@ pub fn part2() -> i64 {
         .product()
 }
 
+/// Print the tree hits of every slope up to `MAX` (1 to 10, default 3), or of the given `RIGHT[/PER],DOWN`
+/// slopes, and the safest one. `--wrap` also wraps the map vertically.
+pub fn report(args: &[String]) -> Result<(), String> {
+    let (wrap, args) = match args {
+        [flag, rest @ ..] if flag == "--wrap" => (Wrap::Both, rest),
+        _ => (Wrap::Horizontal, args),
+    };
+    let explicit = args.first().is_some_and(|arg| arg.contains(','));
+    let slopes: Box<dyn Iterator<Item = Slope>> = match args {
+        [] => Box::new(Slope::all(3)),
+        [max] if !explicit => {
+            let max = match max.parse::<i64>() {
+                Ok(max) if (1..=Slope::MAX_ENUMERATED).contains(&max) => max,
+                Ok(_) => {
+                    return Err(format!(
+                        "Maximum must be from 1 to {}",
+                        Slope::MAX_ENUMERATED
+                    ))
+                }
+                Err(e) => return Err(format!("Invalid maximum '{}': {}", max, e)),
+            };
+            Box::new(Slope::all(max))
+        }
+        slopes => Box::new(
+            slopes
+                .iter()
+                .map(|s| Slope::parse(s))
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter(),
+        ),
+    };
+
+    let map = Map::load();
+    let mut travelled = Vec::new();
+    for slope in slopes {
+        match map.stats(slope, wrap) {
+            Ok(stats) => {
+                println!(
+                    "{:>8}: {:>6} trees in {:>6} squares ({:.1}%)",
+                    slope.to_string(),
+                    stats.trees,
+                    stats.steps,
+                    stats.hit_rate() * 100.0
+                );
+                travelled.push(stats);
+            }
+            // Enumerated slopes that can not be travelled are left out.
+            Err(e) if explicit => return Err(e),
+            Err(_) => {}
+        }
+    }
+    match safest(travelled) {
+        Some(stats) => println!("Safest slope {} hits {} trees", stats.slope, stats.trees),
+        None => println!("No slope can be travelled"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+static EXAMPLE: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#
+";
+
 #[test]
 fn test_part1() {
     assert_eq!(part1(), 153)
@@ -160,3 +429,111 @@ fn test_part1() {
 fn test_part2() {
     assert_eq!(part2(), 2421944712)
 }
+
+#[test]
+fn test_example() {
+    let map = Map::parse(EXAMPLE);
+    let trees = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)].map(|(x, y)| map.test_slope(x, y));
+    assert_eq!(trees, [2, 7, 3, 4, 2]);
+}
+
+#[test]
+fn test_slope_parse() {
+    assert_eq!(Slope::parse("3,1"), Ok(Slope::new(3, 1)));
+    assert_eq!(Slope::parse("-1/2,-3").unwrap().to_string(), "-1/2,-3");
+    assert_eq!(
+        Slope::parse("1/0,1").unwrap_err(),
+        "Invalid slope '1/0,1': divisor must be from 1 to 1000"
+    );
+    assert!(Slope::parse("1/1001,1").is_err());
+    assert!(Slope::parse("3").is_err());
+    assert!(Slope::parse("a,1").is_err());
+}
+
+#[test]
+fn test_fractional_and_negative_slopes() {
+    let map = Map::parse(EXAMPLE);
+    let half = Slope::parse("1/2,1").unwrap();
+    let path = |slope, wrap| map.path(slope, wrap).map(|path| path.collect::<Vec<_>>());
+    assert_eq!(
+        path(half, Wrap::Horizontal).unwrap()[..4],
+        [(0, 1), (1, 2), (1, 3), (2, 4)]
+    );
+    assert_eq!(path(half, Wrap::Horizontal).unwrap().len(), 10);
+
+    // Moving left wraps around to the right edge of the map.
+    let left = path(Slope::new(-3, 1), Wrap::Horizontal).unwrap();
+    assert_eq!(left[..3], [(8, 1), (5, 2), (2, 3)]);
+    assert_eq!(left[3], (10, 4));
+
+    // Large steps can not overflow.
+    let far = Slope {
+        right: i64::MAX,
+        per: Slope::MAX_PER,
+        down: i64::MAX,
+    };
+    assert_eq!(path(far, Wrap::Horizontal).unwrap().len(), 0);
+    assert!(path(far, Wrap::Both).unwrap().len() <= 1000 * 11 * 11);
+
+    assert_eq!(
+        path(Slope::new(1, -1), Wrap::Horizontal).unwrap_err(),
+        "Slope 1,-1 never reaches the bottom"
+    );
+    assert!(path(Slope::new(1, 0), Wrap::Horizontal).is_err());
+}
+
+#[test]
+fn test_vertical_wrap() {
+    let map = Map::parse(EXAMPLE);
+    let path = |slope| map.path(slope, Wrap::Both).unwrap().collect::<Vec<_>>();
+
+    // The example is 11 by 11, so a single step each way takes 11 steps to return to the start.
+    let diagonal = path(Slope::new(1, 1));
+    assert_eq!(diagonal.len(), 11);
+    assert_eq!(diagonal[9..], [(10, 10), (0, 0)]);
+
+    // Moving up wraps around to the bottom row.
+    let up = path(Slope::new(0, -1));
+    assert_eq!(up.len(), 11);
+    assert_eq!(up[0], (0, 10));
+
+    // The fraction has to come back in phase too.
+    assert_eq!(path(Slope::parse("1/2,0").unwrap()).len(), 22);
+    assert_eq!(path(Slope::new(0, 0)), [(0, 0)]);
+    assert_eq!(map.stats(Slope::new(1, 1), Wrap::Both).unwrap().steps, 11);
+}
+
+#[test]
+fn test_safest() {
+    let map = Map::parse(EXAMPLE);
+    let stats = |slopes: &mut dyn Iterator<Item = Slope>| {
+        slopes
+            .filter_map(|slope| map.stats(slope, Wrap::Horizontal).ok())
+            .collect::<Vec<_>>()
+    };
+    let candidates = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)].map(|(x, y)| Slope::new(x, y));
+    let best = safest(stats(&mut candidates.into_iter())).unwrap();
+    assert_eq!(best.slope, Slope::new(1, 1));
+    assert_eq!((best.trees, best.steps), (2, 10));
+    assert_eq!(best.hit_rate(), 0.2);
+
+    let all = stats(&mut Slope::all(3));
+    let best = safest(all.iter().copied()).unwrap();
+    assert!(best.trees <= 2);
+    assert!(all
+        .iter()
+        .all(|stats| stats.steps == 0 || stats.trees >= best.trees));
+
+    assert_eq!(safest([]), None);
+}
+
+#[test]
+fn test_all_slopes_reduced() {
+    let slopes = Slope::all(2).collect::<Vec<_>>();
+    assert!(slopes.contains(&Slope::parse("-1/2,-2").unwrap()));
+    assert!(!slopes.contains(&Slope::parse("2/2,1").unwrap()));
+    assert!(!slopes.contains(&Slope::parse("0/2,1").unwrap()));
+    assert!(slopes.contains(&Slope::new(0, 1)));
+    assert_eq!(slopes.len(), 5 * 7 - 1);
+    assert!(!slopes.contains(&Slope::new(0, 0)));
+}
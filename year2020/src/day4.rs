@@ -1,4 +1,4 @@
-//! Day 4: Passport Processing
+//! # Day 4: Passport Processing
 //!
 //! You arrive at the airport only to realize that you grabbed your North Pole Credentials instead
 //! of your passport. While these documents are extremely similar, North Pole Credentials aren't
@@ -138,185 +138,441 @@
 //! **In your batch file, how many passports are valid?**
 //!
 
+use advent_of_code_core::interval::IntervalSet;
 use std::collections::HashMap;
+use std::fmt;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-enum PassportKey {
-    /// Birth Year
-    BYR,
-    /// Issue Year
-    IYR,
-    /// Expiration Year
-    EYR,
-    /// Height
-    HGT,
-    /// Hair Color
-    HCL,
-    /// Eye Color
-    ECL,
-    /// Passport ID
-    PID,
-    /// Country ID
-    CID,
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Rule {
+    Year(IntervalSet),
+    Units(Vec<(String, IntervalSet)>),
+    HexColor,
+    OneOf(Vec<String>),
+    Digits(usize),
+    Any,
 }
 
-impl PassportKey {
-    fn required(&self) -> bool {
-        match self {
-            PassportKey::BYR => true,
-            PassportKey::IYR => true,
-            PassportKey::EYR => true,
-            PassportKey::HGT => true,
-            PassportKey::HCL => true,
-            PassportKey::ECL => true,
-            PassportKey::PID => true,
-            PassportKey::CID => false,
+impl Rule {
+    fn parse(name: &str, args: &[&str]) -> Result<Rule, String> {
+        let number = |s: &str| {
+            s.parse::<u32>()
+                .map_err(|e| format!("invalid number '{}': {}", s, e))
+        };
+        let bounds = |min: &str, max: &str| -> Result<IntervalSet, String> {
+            let (min, max) = (number(min)? as i64, number(max)? as i64);
+            if min > max {
+                return Err(format!("empty range {}..={}", min, max));
+            }
+            let mut set = IntervalSet::new();
+            set.insert(min..max + 1);
+            Ok(set)
+        };
+
+        match (name, args) {
+            ("year", args) if !args.is_empty() && args.len() % 2 == 0 => args
+                .chunks(2)
+                .map(|c| bounds(c[0], c[1]))
+                .try_fold(IntervalSet::new(), |set, b| Ok(set.union(&b?)))
+                .map(Rule::Year),
+            ("units", args) if !args.is_empty() && args.len() % 3 == 0 => args
+                .chunks(3)
+                .map(|c| Ok((c[0].to_string(), bounds(c[1], c[2])?)))
+                .collect::<Result<_, String>>()
+                .map(Rule::Units),
+            ("hex-color", []) => Ok(Rule::HexColor),
+            ("one-of", values) if !values.is_empty() => {
+                Ok(Rule::OneOf(values.iter().map(|v| v.to_string()).collect()))
+            }
+            ("digits", [n]) => Ok(Rule::Digits(number(n)? as usize)),
+            ("any", []) => Ok(Rule::Any),
+            _ => Err(format!("invalid rule '{} {}'", name, args.join(" "))),
         }
     }
 
-    fn parse(s: &str) -> Result<PassportKey, String> {
-        use PassportKey::*;
-        match &s.to_uppercase()[..] {
-            "BYR" => Ok(BYR),
-            "IYR" => Ok(IYR),
-            "EYR" => Ok(EYR),
-            "HGT" => Ok(HGT),
-            "HCL" => Ok(HCL),
-            "ECL" => Ok(ECL),
-            "PID" => Ok(PID),
-            "CID" => Ok(CID),
-            _ => Err(format!("unexpected key {}", s)),
+    fn check(&self, value: &str) -> Result<(), String> {
+        let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+        match self {
+            Rule::Year(years) => match value.parse::<u32>() {
+                Ok(year) if value.len() == 4 && digits(value) => {
+                    match years.contains(year as i64) {
+                        true => Ok(()),
+                        false => Err(format!("year {} not in {}", year, describe(years, ""))),
+                    }
+                }
+                _ => Err(format!("'{}' is not a four digit year", value)),
+            },
+            Rule::Units(units) => {
+                let split = value
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(value.len());
+                let (number, unit) = value.split_at(split);
+                let Some((_, range)) = units.iter().find(|(u, _)| u == unit) else {
+                    return Err(format!("'{}' does not end with a known unit", value));
+                };
+                match number.parse::<u32>() {
+                    Ok(n) if range.contains(n as i64) => Ok(()),
+                    Ok(n) => Err(format!("{}{} not in {}", n, unit, describe(range, unit))),
+                    Err(_) => Err(format!("'{}' does not start with a number", value)),
+                }
+            }
+            Rule::HexColor => match value.strip_prefix('#') {
+                Some(hex) if hex.len() == 6 && hex.bytes().all(|b| b.is_ascii_hexdigit()) => Ok(()),
+                _ => Err(format!("'{}' is not a hex colour", value)),
+            },
+            Rule::OneOf(values) => match values.iter().any(|v| v == value) {
+                true => Ok(()),
+                false => Err(format!("'{}' is not one of {}", value, values.join(", "))),
+            },
+            Rule::Digits(n) => match value.len() == *n && digits(value) {
+                true => Ok(()),
+                false => Err(format!("'{}' is not {} digits", value, n)),
+            },
+            Rule::Any => Ok(()),
         }
     }
+}
 
-    fn all() -> &'static [PassportKey] {
-        use PassportKey::*;
-        &[BYR, IYR, EYR, HGT, HCL, ECL, PID, CID][..]
+/// Format the allowed values as inclusive ranges, e.g. `150..=193cm`.
+fn describe(set: &IntervalSet, unit: &str) -> String {
+    set.iter()
+        .map(|r| format!("{}..={}{}", r.start, r.end - 1, unit))
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
+
+/// Remove a comment from a schema line. A `#` starts a comment at the start of the line or as a
+/// word of its own, so values starting with `#` like `#fff` are kept.
+fn strip_comment(line: &str) -> &str {
+    let comment = line.match_indices('#').find(|&(i, _)| {
+        let (before, after) = (&line[..i], &line[i + 1..]);
+        let word = before.ends_with(char::is_whitespace)
+            && (after.is_empty() || after.starts_with(char::is_whitespace));
+        before.trim().is_empty() || word
+    });
+    match comment {
+        Some((i, _)) => &line[..i],
+        None => line,
     }
 }
 
-fn init() -> Vec<HashMap<PassportKey, &'static str>> {
-    let mut list = Vec::new();
-    let mut passport = HashMap::new();
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Field {
+    name: String,
+    required: bool,
+    rule: Rule,
+}
 
-    for line in include_str!("day4.txt").lines() {
-        if line.is_empty() {
-            list.push(passport);
-            passport = HashMap::new();
-            continue;
-        }
+/// Passport fields and the rules their values must follow, see `day4.schema` for the format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Schema {
+    fields: Vec<Field>,
+}
 
-        for pair in line.split_whitespace() {
-            let mut split = pair.split(':');
-            let key = PassportKey::parse(split.next().unwrap()).unwrap();
-            let value = split.next().unwrap();
-            passport.insert(key, value);
-        }
+impl Schema {
+    fn parse(s: &str) -> Result<Schema, String> {
+        let fields = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i, strip_comment(line).trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(i, line)| {
+                let mut words = line.split_whitespace().collect::<Vec<_>>();
+                let name = words.remove(0).to_string();
+                let required = words.first() != Some(&"optional");
+                if !required {
+                    words.remove(0);
+                }
+                let Some((&rule, args)) = words.split_first() else {
+                    return Err(format!("Line {}: missing rule for '{}'", i + 1, name));
+                };
+                let rule = Rule::parse(rule, args).map_err(|e| format!("Line {}: {}", i + 1, e))?;
+                Ok(Field {
+                    name,
+                    required,
+                    rule,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Schema { fields })
     }
 
-    list.push(passport);
-    list
-}
+    fn load(path: &str) -> Result<Schema, String> {
+        let s =
+            std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Schema::parse(&s)
+    }
 
-fn validate_year(
-    value: &'static str,
-    min: u16,
-    max: u16,
-) -> Result<(), Box<dyn std::error::Error>> {
-    if value.len() != 4 {
-        return Err(format!("Not four digit year {:?}", value).into());
+    /// Whether every field of the schema is present and valid. Fields the schema does not know
+    /// about are ignored, as in the puzzle.
+    fn is_valid(&self, passport: &Passport) -> bool {
+        self.validate(passport)
+            .iter()
+            .all(|v| v.reason == Reason::Unknown)
     }
 
-    let value = value.parse::<u16>()?;
+    fn validate(&self, passport: &Passport) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for field in &self.fields {
+            let reason = match passport.get(&field.name[..]) {
+                None if field.required => Reason::Missing,
+                None => continue,
+                Some(value) => match field.rule.check(value) {
+                    Ok(()) => continue,
+                    Err(e) => Reason::Invalid(e),
+                },
+            };
+            violations.push(Violation {
+                field: field.name.clone(),
+                reason,
+            });
+        }
 
-    if (min..=max).contains(&value) {
-        Ok(())
-    } else {
-        Err(format!("Year out of range {:?}", value).into())
+        let mut unknown = passport
+            .keys()
+            .filter(|k| !self.fields.iter().any(|f| f.name == **k))
+            .collect::<Vec<_>>();
+        unknown.sort();
+        violations.extend(unknown.into_iter().map(|k| Violation {
+            field: k.to_string(),
+            reason: Reason::Unknown,
+        }));
+
+        violations
     }
 }
 
-fn validate(key: PassportKey, value: &'static str) -> Result<(), Box<dyn std::error::Error>> {
-    use PassportKey::*;
-    match key {
-        BYR => validate_year(value, 1920, 2002),
-        IYR => validate_year(value, 2010, 2020),
-        EYR => validate_year(value, 2020, 2030),
-        HGT => {
-            if value.ends_with("cm") {
-                let value = value.trim_end_matches("cm").parse::<u16>()?;
-                if (150..=193).contains(&value) {
-                    Ok(())
-                } else {
-                    Err(format!("Height out of range {:?}", value).into())
-                }
-            } else if value.ends_with("in") {
-                let value = value.trim_end_matches("in").parse::<u16>()?;
-                if (59..=76).contains(&value) {
-                    Ok(())
-                } else {
-                    Err(format!("Height out of range {:?}", value).into())
-                }
-            } else {
-                Err(format!("unexpected Height suffix {:?}", value).into())
-            }
-        }
-        HCL => {
-            if value.starts_with('#') && value.len() == 7 {
-                u64::from_str_radix(value.trim_start_matches('#'), 16)
-                    .map(|_| ())
-                    .map_err(|e| e.into())
-            } else {
-                Err(format!("Invalid hair color {:?}", value).into())
-            }
-        }
-        ECL => match value {
-            "amb" => Ok(()),
-            "blu" => Ok(()),
-            "brn" => Ok(()),
-            "gry" => Ok(()),
-            "grn" => Ok(()),
-            "hzl" => Ok(()),
-            "oth" => Ok(()),
-            _ => Err(format!("Invalid eye color {:?}", value).into()),
-        },
-        PID => {
-            if value.len() == 9 && value.chars().all(|c| c.is_numeric()) {
-                Ok(())
-            } else {
-                Err(format!("Invalid Passport ID {:?}", value).into())
-            }
+impl Default for Schema {
+    fn default() -> Schema {
+        Schema::parse(include_str!("day4.schema")).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reason {
+    Missing,
+    Unknown,
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Violation {
+    field: String,
+    reason: Reason,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.reason {
+            Reason::Missing => write!(f, "{}: missing", self.field),
+            Reason::Unknown => write!(f, "{}: not in schema", self.field),
+            Reason::Invalid(reason) => write!(f, "{}: {}", self.field, reason),
         }
-        _ => Err(format!("unknown key {:?}", key).into()),
     }
 }
 
+type Passport<'a> = HashMap<&'a str, &'a str>;
+
+fn parse(s: &str) -> Vec<Passport<'_>> {
+    s.split("\n\n")
+        .map(|block| {
+            block
+                .split_whitespace()
+                .map(|pair| pair.split_once(':').unwrap())
+                .collect()
+        })
+        .collect()
+}
+
 pub fn part1() -> i64 {
-    init()
+    let schema = Schema::default();
+    parse(include_str!("day4.txt"))
         .iter()
         .filter(|passport| {
-            PassportKey::all()
+            schema
+                .validate(passport)
                 .iter()
-                .filter(|k| k.required())
-                .all(|k| passport.contains_key(k))
+                .all(|v| v.reason != Reason::Missing)
         })
         .count() as i64
 }
 
 pub fn part2() -> i64 {
-    init()
+    let schema = Schema::default();
+    parse(include_str!("day4.txt"))
         .iter()
-        .filter(|passport| {
-            PassportKey::all().iter().filter(|k| k.required()).all(|k| {
-                passport
-                    .get(k)
-                    .map(|value| validate(*k, value).is_ok())
-                    .unwrap_or(false)
-            })
-        })
+        .filter(|passport| schema.is_valid(passport))
         .count() as i64
 }
 
+/// Runner command: list the violations of every passport in the puzzle input, including fields
+/// not in the schema, which do not make a passport invalid.
+pub fn report(args: &[String]) -> Result<(), String> {
+    let schema = match args {
+        [] => Schema::default(),
+        [path] => Schema::load(path)?,
+        _ => return Err("Usage: passports [SCHEMA]".to_string()),
+    };
+
+    let passports = parse(include_str!("day4.txt"));
+    let mut valid = 0;
+    for (i, passport) in passports.iter().enumerate() {
+        let violations = schema.validate(passport);
+        if schema.is_valid(passport) {
+            valid += 1;
+        }
+        if violations.is_empty() {
+            continue;
+        }
+        println!("Passport {}:", i + 1);
+        for violation in violations {
+            println!("\t{}", violation);
+        }
+    }
+    println!("{} of {} passports are valid", valid, passports.len());
+    Ok(())
+}
+
+#[cfg(test)]
+static INVALID: &str = "\
+eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+iyr:2019
+hcl:#602927 eyr:1967 hgt:170cm
+ecl:grn pid:012533040 byr:1946
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+
+hgt:59cm ecl:zzz
+eyr:2038 hcl:74454a iyr:2023
+pid:3556412378 byr:2007
+";
+
+#[cfg(test)]
+static VALID: &str = "\
+pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:#888785
+hgt:164cm byr:2001 iyr:2015 cid:88
+pid:545766238 ecl:hzl
+eyr:2022
+
+iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719
+";
+
+#[test]
+fn test_rules() {
+    let schema = Schema::default();
+    let check = |field: &str, value: &str| {
+        let field = schema.fields.iter().find(|f| f.name == field).unwrap();
+        field.rule.check(value).is_ok()
+    };
+
+    assert!(check("byr", "2002"));
+    assert!(!check("byr", "2003"));
+    assert!(check("hgt", "60in"));
+    assert!(check("hgt", "190cm"));
+    assert!(!check("hgt", "190in"));
+    assert!(!check("hgt", "190"));
+    assert!(check("hcl", "#123abc"));
+    assert!(!check("hcl", "#123abz"));
+    assert!(!check("hcl", "123abc"));
+    assert!(check("ecl", "brn"));
+    assert!(!check("ecl", "wat"));
+    assert!(check("pid", "000000001"));
+    assert!(!check("pid", "0123456789"));
+
+    let rule = Rule::parse("year", &["1920", "1950", "1970", "2002"]).unwrap();
+    assert!(rule.check("1950").is_ok());
+    assert!(rule.check("1970").is_ok());
+    assert_eq!(
+        rule.check("1960"),
+        Err("year 1960 not in 1920..=1950 or 1970..=2002".to_string())
+    );
+}
+
+#[test]
+fn test_examples() {
+    let schema = Schema::default();
+    assert!(parse(VALID).iter().all(|p| schema.validate(p).is_empty()));
+    assert!(parse(INVALID).iter().all(|p| !schema.is_valid(p)));
+
+    // Fields the schema does not know are reported, but do not make a passport invalid.
+    let extra =
+        parse("pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980\nhcl:#623a2f foo:1\n");
+    assert!(schema.is_valid(&extra[0]));
+    assert_eq!(schema.validate(&extra[0]).len(), 1);
+}
+
+#[test]
+fn test_violations() {
+    let schema = Schema::default();
+    let passport = parse("hgt:59cm ecl:zzz\neyr:2038 hcl:74454a iyr:2023\npid:3556412378 foo:1\n");
+    let violations = schema
+        .validate(&passport[0])
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        violations,
+        &[
+            "byr: missing",
+            "iyr: year 2023 not in 2010..=2020",
+            "eyr: year 2038 not in 2020..=2030",
+            "hgt: 59cm not in 150..=193cm",
+            "hcl: '74454a' is not a hex colour",
+            "ecl: 'zzz' is not one of amb, blu, brn, gry, grn, hzl, oth",
+            "pid: '3556412378' is not 9 digits",
+            "foo: not in schema",
+        ]
+    );
+}
+
+#[test]
+fn test_schema_errors() {
+    assert_eq!(
+        Schema::parse("byr year 1920\n"),
+        Err("Line 1: invalid rule 'year 1920'".to_string())
+    );
+    assert_eq!(
+        Schema::parse("# comment\n\ncid optional\n"),
+        Err("Line 3: missing rule for 'cid'".to_string())
+    );
+    assert!(Schema::parse("hgt units cm 150 x\n")
+        .unwrap_err()
+        .starts_with("Line 1: invalid number 'x'"));
+    assert_eq!(
+        Schema::parse("byr year 2002 1920\n"),
+        Err("Line 1: empty range 2002..=1920".to_string())
+    );
+
+    // Values may start with '#', a comment is a '#' on its own or at the start of the line.
+    let schema = Schema::parse("  # colours\nhcl one-of #fff #000 # black or white\n").unwrap();
+    assert_eq!(
+        schema.fields[0].rule,
+        Rule::OneOf(vec!["#fff".to_string(), "#000".to_string()])
+    );
+    assert_eq!(strip_comment("hcl hex-color #"), "hcl hex-color ");
+    assert_eq!(strip_comment("#one-of a"), "");
+
+    let schema = Schema::parse("cid optional any # ignored\n").unwrap();
+    assert_eq!(
+        schema.fields,
+        &[Field {
+            name: "cid".to_string(),
+            required: false,
+            rule: Rule::Any,
+        }]
+    );
+}
+
 #[test]
 fn test_part1() {
     assert_eq!(part1(), 233)
@@ -70,86 +70,268 @@
 //! **What is the ID of your seat?**
 //!
 
-use bit_vec::BitVec;
+use advent_of_code_core::bitset::BitSet;
+use advent_of_code_core::visual::{self, Color, Frame};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Seat {
     row: u16,
     column: u16,
 }
 
-impl Seat {
-    fn parse(s: &str) -> Seat {
-        assert_eq!(s.len(), 10);
-        let (row_str, column_str) = s.split_at(7);
+/// The size of the plane and the letters selecting the lower and upper half in a boarding pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Layout {
+    row_bits: u32,
+    column_bits: u32,
+    row_letters: (char, char),
+    column_letters: (char, char),
+}
 
-        let row = row_str
-            .chars()
-            .map(|c| match c {
-                'B' => true,
-                'F' => false,
-                _ => panic!("unknown character {}", c),
-            })
-            .rev()
-            .enumerate()
-            .fold(0u16, |acc, (i, c)| acc | ((c as u16) << i));
-
-        let column = column_str
-            .chars()
-            .map(|c| match c {
-                'R' => true,
-                'L' => false,
-                _ => panic!("unknown character {}", c),
+impl Layout {
+    const STANDARD: Layout = Layout {
+        row_bits: 7,
+        column_bits: 3,
+        row_letters: ('F', 'B'),
+        column_letters: ('L', 'R'),
+    };
+
+    /// A layout with passes of `row_bits` letters for the row followed by `column_bits` for the
+    /// column. Seats store both as `u16`, so neither may be wider than 16 bits.
+    fn new(
+        row_bits: u32,
+        column_bits: u32,
+        row_letters: (char, char),
+        column_letters: (char, char),
+    ) -> Result<Layout, String> {
+        for (name, bits) in [("row", row_bits), ("column", column_bits)] {
+            if bits > u16::BITS {
+                return Err(format!(
+                    "A {} of {} bits is wider than {} bits",
+                    name,
+                    bits,
+                    u16::BITS
+                ));
+            }
+        }
+        for (lower, upper) in [row_letters, column_letters] {
+            if lower == upper {
+                return Err(format!("The letters for both halves are '{}'", lower));
+            }
+        }
+
+        Ok(Layout {
+            row_bits,
+            column_bits,
+            row_letters,
+            column_letters,
+        })
+    }
+
+    fn rows(&self) -> usize {
+        1 << self.row_bits
+    }
+
+    fn columns(&self) -> usize {
+        1 << self.column_bits
+    }
+
+    fn seats(&self) -> usize {
+        self.rows() * self.columns()
+    }
+
+    fn id(&self, seat: Seat) -> usize {
+        seat.row as usize * self.columns() + seat.column as usize
+    }
+
+    fn seat(&self, id: usize) -> Seat {
+        Seat {
+            row: (id / self.columns()) as u16,
+            column: (id % self.columns()) as u16,
+        }
+    }
+
+    fn decode(&self, pass: &str) -> Result<Seat, String> {
+        let len = (self.row_bits + self.column_bits) as usize;
+        if pass.chars().count() != len {
+            return Err(format!("Boarding pass '{}' is not {} letters", pass, len));
+        }
+
+        let bits = |s: &str, (lower, upper): (char, char)| {
+            s.chars().try_fold(0u16, |acc, c| match c {
+                c if c == lower => Ok(acc << 1),
+                c if c == upper => Ok(acc << 1 | 1),
+                _ => Err(format!(
+                    "Unexpected '{}' in '{}', expected {} or {}",
+                    c, pass, lower, upper
+                )),
             })
-            .rev()
-            .enumerate()
-            .fold(0u16, |acc, (i, c)| acc | ((c as u16) << i));
+        };
+
+        let split = pass
+            .char_indices()
+            .nth(self.row_bits as usize)
+            .map_or(pass.len(), |(i, _)| i);
+        let (row, column) = pass.split_at(split);
+        Ok(Seat {
+            row: bits(row, self.row_letters)?,
+            column: bits(column, self.column_letters)?,
+        })
+    }
+
+    fn encode(&self, seat: Seat) -> Result<String, String> {
+        if seat.row as usize >= self.rows() || seat.column as usize >= self.columns() {
+            return Err(format!("{:?} is outside the plane", seat));
+        }
+
+        let letters = |value: u16, bits: u32, (lower, upper): (char, char)| {
+            (0..bits)
+                .rev()
+                .map(move |i| if value >> i & 1 == 1 { upper } else { lower })
+        };
+        Ok(letters(seat.row, self.row_bits, self.row_letters)
+            .chain(letters(seat.column, self.column_bits, self.column_letters))
+            .collect())
+    }
+}
+
+struct SeatMap {
+    layout: Layout,
+    occupied: BitSet,
+}
+
+impl SeatMap {
+    fn new(layout: Layout, seats: impl IntoIterator<Item = Seat>) -> SeatMap {
+        SeatMap {
+            layout,
+            occupied: seats.into_iter().map(|seat| layout.id(seat)).collect(),
+        }
+    }
+
+    fn is_occupied(&self, id: usize) -> bool {
+        self.occupied.contains(id)
+    }
+
+    fn free_seats(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.layout.seats()).filter(|&id| !self.is_occupied(id))
+    }
 
-        Seat { row, column }
+    /// Free seats with both neighbouring ids occupied.
+    fn gaps(&self) -> impl Iterator<Item = usize> + '_ {
+        self.free_seats()
+            .filter(|&id| id > 0 && self.is_occupied(id - 1) && self.is_occupied(id + 1))
     }
 
-    fn id(&self) -> i64 {
-        self.row as i64 * 8 + self.column as i64
+    fn occupancy(&self) -> f64 {
+        self.occupied.len() as f64 / self.layout.seats() as f64
+    }
+
+    /// One line per row with occupied seats as `#` and gaps marked `O`.
+    fn draw(&self) -> Frame {
+        let mut frame = Frame::new(self.layout.columns(), self.layout.rows());
+        for id in self.occupied.iter() {
+            let seat = self.layout.seat(id);
+            frame.set(seat.column as i64, seat.row as i64, '#');
+        }
+        for id in self.gaps() {
+            let seat = self.layout.seat(id);
+            frame.mark(seat.column as i64, seat.row as i64, 'O', Color::Green);
+        }
+        frame
     }
 }
 
-fn init() -> Vec<Seat> {
-    include_str!("day5.txt")
+fn parse(input: &str, layout: &Layout) -> Result<Vec<Seat>, String> {
+    input
         .lines()
-        .map(|s| Seat::parse(s))
-        .collect::<Vec<_>>()
+        .enumerate()
+        .map(|(i, pass)| {
+            layout
+                .decode(pass)
+                .map_err(|e| format!("Line {}: {}", i + 1, e))
+        })
+        .collect()
+}
+
+fn init(layout: &Layout) -> Vec<Seat> {
+    parse(include_str!("day5.txt"), layout).unwrap()
 }
 
 pub fn part1() -> i64 {
-    init().iter().map(|s| s.id()).max().unwrap() as i64
+    let layout = Layout::STANDARD;
+    init(&layout)
+        .into_iter()
+        .map(|s| layout.id(s))
+        .max()
+        .unwrap() as i64
 }
 
 pub fn part2() -> i64 {
-    let mut grid = BitVec::from_elem(8 * 128, false);
-    init()
-        .iter()
-        .for_each(|seat| grid.set(seat.id() as usize, true));
-
-    let exists = |idx: i64| {
-        if (0..8 * 128).contains(&idx) {
-            grid.get(idx as usize).unwrap()
-        } else {
-            false
-        }
+    let layout = Layout::STANDARD;
+    let map = SeatMap::new(layout, init(&layout));
+    visual::frame(|| map.draw());
+
+    let gaps = map.gaps().collect::<Vec<_>>();
+    assert_eq!(gaps.len(), 1, "expected a single gap, found {:?}", gaps);
+    gaps[0] as i64
+}
+
+/// Runner command: print the seat map of the puzzle input or `FILE` with occupancy and gaps. The
+/// flags change the number of row and column letters and the letters themselves, given as
+/// `ROW_LOWER ROW_UPPER COLUMN_LOWER COLUMN_UPPER`.
+pub fn report(args: &[String]) -> Result<(), String> {
+    let usage =
+        || "Usage: seats [--rows=BITS] [--columns=BITS] [--letters=FBLR] [FILE]".to_string();
+    let bits = |s: &str| {
+        s.parse::<u32>()
+            .map_err(|e| format!("Invalid width '{}': {}", s, e))
     };
 
-    (0..8 * 128)
-        .filter(|&id| !exists(id) && exists(id + 1) && exists(id - 1))
-        .next()
-        .unwrap()
+    let standard = Layout::STANDARD;
+    let (mut rows, mut columns) = (standard.row_bits, standard.column_bits);
+    let (mut row_letters, mut column_letters) = (standard.row_letters, standard.column_letters);
+    let mut file = None;
+    for arg in args {
+        match arg.split_once('=') {
+            Some(("--rows", n)) => rows = bits(n)?,
+            Some(("--columns", n)) => columns = bits(n)?,
+            Some(("--letters", letters)) => match letters.chars().collect::<Vec<_>>()[..] {
+                [a, b, c, d] => (row_letters, column_letters) = ((a, b), (c, d)),
+                _ => return Err(format!("Expected four letters, got '{}'", letters)),
+            },
+            _ if file.is_none() && !arg.starts_with("--") => file = Some(arg),
+            _ => return Err(usage()),
+        }
+    }
+
+    let layout = Layout::new(rows, columns, row_letters, column_letters)?;
+    let input = match file {
+        Some(file) => std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?,
+        None => include_str!("day5.txt").to_string(),
+    };
+    let map = SeatMap::new(layout, parse(&input, &layout)?);
+    print!("{}", map.draw().render());
+    println!(
+        "{} of {} seats occupied ({:.1}%)",
+        map.occupied.len(),
+        layout.seats(),
+        map.occupancy() * 100.0
+    );
+    for id in map.gaps() {
+        println!("Gap at seat {}: {}", id, layout.encode(layout.seat(id))?);
+    }
+    Ok(())
 }
 
 #[test]
 fn test_seat_parsing() {
-    let test_seat = |str: &str, row: u16, column: u16, id: i64| {
-        let seat = Seat::parse(str);
+    let layout = Layout::STANDARD;
+    let test_seat = |str: &str, row: u16, column: u16, id: usize| {
+        let seat = layout.decode(str).unwrap();
         assert_eq!(seat.row, row);
         assert_eq!(seat.column, column);
-        assert_eq!(seat.id(), id)
+        assert_eq!(layout.id(seat), id);
+        assert_eq!(layout.encode(seat).unwrap(), str);
     };
 
     test_seat("FBFBBFFRLR", 44, 5, 357);
@@ -158,6 +340,90 @@ fn test_seat_parsing() {
     test_seat("BBFFBBFRLL", 102, 4, 820);
 }
 
+#[test]
+fn test_round_trip() {
+    let layouts = [
+        Layout::STANDARD,
+        Layout::new(3, 2, ('a', 'b'), ('0', '1')).unwrap(),
+        Layout::new(0, 4, ('F', 'B'), ('L', 'R')).unwrap(),
+        Layout::new(9, 5, ('↑', '↓'), ('←', '→')).unwrap(),
+    ];
+
+    // Every seat encodes to a distinct pass that decodes back to the same seat.
+    for layout in layouts {
+        let mut passes = Vec::with_capacity(layout.seats());
+        for id in 0..layout.seats() {
+            let seat = layout.seat(id);
+            assert_eq!(layout.id(seat), id);
+            let pass = layout.encode(seat).unwrap();
+            assert_eq!(layout.decode(&pass), Ok(seat));
+            passes.push(pass);
+        }
+        passes.sort();
+        passes.dedup();
+        assert_eq!(passes.len(), layout.seats());
+    }
+}
+
+#[test]
+fn test_codec_errors() {
+    let layout = Layout::STANDARD;
+    assert_eq!(
+        layout.decode("FBFBBFFRL"),
+        Err("Boarding pass 'FBFBBFFRL' is not 10 letters".to_string())
+    );
+    assert_eq!(
+        layout.decode("FBFBBFRRLR"),
+        Err("Unexpected 'R' in 'FBFBBFRRLR', expected F or B".to_string())
+    );
+    assert!(layout
+        .encode(Seat {
+            row: 128,
+            column: 0
+        })
+        .is_err());
+    assert!(layout.encode(Seat { row: 0, column: 8 }).is_err());
+}
+
+#[test]
+fn test_layout_limits() {
+    // The widest layout decodes the last row and column without truncating.
+    let layout = Layout::new(16, 16, ('F', 'B'), ('L', 'R')).unwrap();
+    let pass = "B".repeat(16) + &"R".repeat(16);
+    assert_eq!(
+        layout.decode(&pass),
+        Ok(Seat {
+            row: u16::MAX,
+            column: u16::MAX
+        })
+    );
+
+    assert_eq!(
+        Layout::new(17, 3, ('F', 'B'), ('L', 'R')),
+        Err("A row of 17 bits is wider than 16 bits".to_string())
+    );
+    assert_eq!(
+        Layout::new(7, 32, ('F', 'B'), ('L', 'R')),
+        Err("A column of 32 bits is wider than 16 bits".to_string())
+    );
+    assert_eq!(
+        Layout::new(7, 3, ('F', 'B'), ('L', 'L')),
+        Err("The letters for both halves are 'L'".to_string())
+    );
+}
+
+#[test]
+fn test_seat_map() {
+    let layout = Layout::new(1, 2, ('F', 'B'), ('L', 'R')).unwrap();
+    let seats = ["FLR", "FRL", "BLL", "BRL", "BRR"].map(|p| layout.decode(p).unwrap());
+    let map = SeatMap::new(layout, seats);
+
+    assert_eq!(map.free_seats().collect::<Vec<_>>(), &[0, 3, 5]);
+    assert_eq!(map.gaps().collect::<Vec<_>>(), &[3, 5]);
+    assert_eq!(map.occupancy(), 5.0 / 8.0);
+    assert_eq!(map.draw().to_string(), ".##O\n#O##\n");
+}
+
 #[test]
 fn test_part1() {
     assert_eq!(part1(), 963)
@@ -46,13 +46,13 @@
 //!
 //! This list represents answers from five groups:
 //! - The first group contains one person who answered "yes" to **`3`** questions:
-//! `a`, `b`, and `c`.
+//!   `a`, `b`, and `c`.
 //! - The second group contains three people; combined, they answered "yes" to **`3`** questions:
-//! `a`, `b`, and `c`.
+//!   `a`, `b`, and `c`.
 //! - The third group contains two people; combined, they answered "yes" to **`3`** questions:
-//! `a`, `b`, and `c`.
+//!   `a`, `b`, and `c`.
 //! - The fourth group contains four people; combined, they answered "yes" to only **`1`** question,
-//! `a`.
+//!   `a`.
 //! - The last group contains one person who answered "yes" to only **`1`** question, `b`.
 //!
 //! In this example, the sum of these counts is `3 + 3 + 3 + 1 + 1` = **`11`**.
@@ -91,10 +91,10 @@
 //! This list represents answers from five groups:
 //!
 //! - In the first group, everyone (all 1 person) answered "yes" to **`3`** questions:
-//! `a`, `b`, and `c`.
+//!   `a`, `b`, and `c`.
 //! - In the second group, there is **no** question to which everyone answered "yes".
 //! - In the third group, everyone answered yes to only **`1`** question, `a`.
-//! Since some people did not answer "yes" to `b` or `c`, they don't count.
+//!   Since some people did not answer "yes" to `b` or `c`, they don't count.
 //! - In the fourth group, everyone answered yes to only **`1`** question, `a`.
 //! - In the fifth group, everyone (all 1 person) answered "yes" to **`1`** question, `b`.
 //!
@@ -103,39 +103,62 @@
 //! For each group, count the number of questions to which **everyone** answered "yes".
 //! **What is the sum of those counts?**
 
-fn init() -> Vec<(u32, u32)> {
+use advent_of_code_core::bitset::BitSet;
+use advent_of_code_core::group::Group;
+
+fn parse(input: &str) -> Vec<Group> {
     let mut list = Vec::new();
-    let mut group: (u32, u32) = (0, u32::max_value());
-    for line in include_str!("day6.txt").lines() {
+    let mut group = Group::new();
+    for line in input.lines() {
         if line.is_empty() {
-            list.push(group);
-            group = (0, u32::max_value());
+            list.push(std::mem::take(&mut group));
             continue;
         }
 
         let person = line
             .chars()
-            .map(|c| {
-                assert!(('a'..='z').contains(&c));
-                c as u32 - 'a' as u32
+            .map(|c: char| {
+                assert!(c.is_ascii_lowercase());
+                c as usize - 'a' as usize
             })
-            .fold(0u32, |acc, i| acc | 1 << i);
-
-        group.0 |= person;
-        group.1 &= person;
+            .collect::<BitSet>();
+        group.push(person);
     }
 
     list.push(group);
     list
 }
 
+fn init() -> Vec<Group> {
+    parse(include_str!("day6.txt"))
+}
+
 pub fn part1() -> i64 {
-    init().iter().map(|i| i.0.count_ones()).sum::<u32>() as i64
+    init().iter().map(|g| g.union().len()).sum::<usize>() as i64
 }
 
 pub fn part2() -> i64 {
-    init().iter().map(|i| i.1.count_ones()).sum::<u32>() as i64
+    init().iter().map(|g| g.intersection().len()).sum::<usize>() as i64
 }
+
+#[cfg(test)]
+static EXAMPLE: &str = "abc
+
+a
+b
+c
+
+ab
+ac
+
+a
+a
+a
+a
+
+b
+";
+
 #[test]
 fn test_part1() {
     assert_eq!(part1(), 6680)
@@ -145,3 +168,13 @@ fn test_part1() {
 fn test_part2() {
     assert_eq!(part2(), 3117)
 }
+
+#[test]
+fn test_example() {
+    let groups = parse(EXAMPLE);
+    let sum = |f: fn(&Group) -> BitSet| groups.iter().map(|g| f(g).len()).sum::<usize>();
+    assert_eq!(sum(Group::union), 11);
+    assert_eq!(sum(Group::intersection), 6);
+    assert_eq!(sum(Group::symmetric_difference), 9);
+    assert_eq!(sum(|g| g.at_least(2)), 2);
+}
@@ -16,14 +16,14 @@
 //! (a signed number like `+4` or `-20`).
 //!
 //! - `acc` increases or decreases a single global value called the accumulator by the value given
-//! in the argument. For example, `acc +7` would increase the accumulator by 7. The accumulator
-//! starts at 0. After an acc instruction, the instruction immediately below it is executed next.
+//!   in the argument. For example, `acc +7` would increase the accumulator by 7. The accumulator
+//!   starts at 0. After an acc instruction, the instruction immediately below it is executed next.
 //! - `jmp` **jumps** to a new instruction relative to itself. The next instruction to execute is
-//! found using the argument as an offset from the jmp instruction; for example, `jmp +2` would
-//! skip the next instruction, `jmp +1` would continue to the instruction immediately below it,
-//! and `jmp -20` would cause the instruction 20 lines above to be executed next.
+//!   found using the argument as an offset from the jmp instruction; for example, `jmp +2` would
+//!   skip the next instruction, `jmp +1` would continue to the instruction immediately below it,
+//!   and `jmp -20` would cause the instruction 20 lines above to be executed next.
 //! - `nop` stands for **No OPeration** - it does nothing. The instruction immediately
-//! below it is executed next.
+//!   below it is executed next.
 //!
 //! For example, consider the following program:
 //!
@@ -125,90 +125,134 @@
 //! program terminates?**
 //!
 
-use bit_vec::BitVec;
+use std::io;
 
-#[derive(Debug, Clone, Copy)]
-enum Instruction {
-    ACC(i16),
-    NOP(i16),
-    JMP(i16),
+use advent_of_code_core::bitset::BitSet;
+
+use crate::console::{self, Exit, Instruction, Machine, OpcodeTable, Stop};
+
+fn load(table: &OpcodeTable) -> Vec<Instruction> {
+    table.assemble(include_str!("day8.txt")).unwrap()
 }
 
-fn load() -> Vec<Instruction> {
-    include_str!("day8.txt")
-        .lines()
-        .map(|s| {
-            let (opcode, param) = s.split_at(3);
-            let (sign, value_str) = param.split_at(2);
-            let value = value_str.parse::<i16>().unwrap();
-            match (opcode, sign) {
-                ("acc", " +") => Instruction::ACC(value),
-                ("acc", " -") => Instruction::ACC(-value),
-                ("nop", " +") => Instruction::NOP(value),
-                ("nop", " -") => Instruction::NOP(-value),
-                ("jmp", " +") => Instruction::JMP(value),
-                ("jmp", " -") => Instruction::JMP(-value),
-                _ => panic!("invalid instruction '{}'", s),
-            }
-        })
-        .collect::<Vec<_>>()
+fn run(table: &OpcodeTable, program: &[Instruction]) -> Result<i64, i64> {
+    let mut machine = Machine::new(table, program);
+    match machine.run() {
+        Stop::Exit(Exit::Terminated) => Ok(machine.state().acc),
+        _ => Err(machine.state().acc),
+    }
 }
 
-fn run(instructions: &[Instruction]) -> Result<i64, i64> {
-    let mut executed = BitVec::from_elem(instructions.len(), false);
-    let mut acc = 0i64;
-    let mut pc = 0i64;
-    loop {
-        if pc == instructions.len() as i64 {
-            return Ok(acc);
-        } else if pc < 0 || pc > instructions.len() as i64 {
-            return Err(acc);
-        }
+pub fn part1() -> i64 {
+    let table = OpcodeTable::handheld();
+    run(&table, &load(&table)).unwrap_err()
+}
 
-        if executed.get(pc as usize).unwrap() {
-            return Err(acc);
-        }
+/// Replacing the instruction at `pc` with opcode `op` makes the program terminate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Repair {
+    pc: usize,
+    op: u8,
+}
 
-        executed.set(pc as usize, true);
+/// Instructions that lead to termination, including the end of the program at index `len`.
+///
+/// Every instruction has a single successor, so this is a reverse search from the end of the
+/// program over the control flow graph.
+fn reaches_end(table: &OpcodeTable, program: &[Instruction]) -> BitSet {
+    let len = program.len();
+    let mut predecessors = vec![Vec::new(); len + 1];
+    for (pc, instruction) in program.iter().enumerate() {
+        let next = table.successor(*instruction, pc as i64);
+        if (0..=len as i64).contains(&next) {
+            predecessors[next as usize].push(pc);
+        }
+    }
 
-        match instructions[pc as usize] {
-            Instruction::JMP(value) => pc += value as i64,
-            Instruction::NOP(_) => pc += 1,
-            Instruction::ACC(value) => {
-                pc += 1;
-                acc += value as i64;
+    let mut reached = BitSet::with_capacity(len + 1);
+    let mut queue = vec![len];
+    reached.insert(len);
+    while let Some(pc) = queue.pop() {
+        for &prev in &predecessors[pc] {
+            if reached.insert(prev) {
+                queue.push(prev);
             }
         }
     }
+    reached
 }
 
-pub fn part1() -> i64 {
-    run(&load()[..]).unwrap_err()
+/// Every single `jmp`/`nop` swap that makes a looping program terminate.
+///
+/// A swap can only matter on the path the program takes before it loops, and it fixes the
+/// program exactly when the new successor already reaches the end. That path can not pass the
+/// swapped instruction again, as it would then have reached the end without the swap.
+fn find_repairs(table: &OpcodeTable, program: &[Instruction]) -> Vec<Repair> {
+    let (jmp, nop) = (table.lookup("jmp").unwrap(), table.lookup("nop").unwrap());
+    let reached = reaches_end(table, program);
+
+    let mut machine = Machine::new(table, program);
+    machine.enable_trace();
+    if machine.run() == Stop::Exit(Exit::Terminated) {
+        return Vec::new();
+    }
+
+    machine
+        .trace()
+        .iter()
+        .filter_map(|entry| {
+            let op = match entry.instruction.op {
+                op if op == jmp => nop,
+                op if op == nop => jmp,
+                _ => return None,
+            };
+            let swapped = Instruction {
+                op,
+                ..entry.instruction
+            };
+            let next = table.successor(swapped, entry.pc as i64);
+            (next >= 0 && reached.contains(next as usize)).then_some(Repair { pc: entry.pc, op })
+        })
+        .collect()
+}
+
+/// Find the only repair of the program and the accumulator after running the repaired program.
+fn repair(table: &OpcodeTable, program: &[Instruction]) -> Result<(Repair, i64), String> {
+    let repairs = find_repairs(table, program);
+    let repair = match repairs[..] {
+        [repair] => repair,
+        [] => return Err("No single instruction swap makes the program terminate".to_string()),
+        _ => {
+            let pcs = repairs.iter().map(|r| r.pc.to_string()).collect::<Vec<_>>();
+            return Err(format!("Ambiguous repair, candidates: {}", pcs.join(", ")));
+        }
+    };
+
+    let mut repaired = program.to_vec();
+    repaired[repair.pc].op = repair.op;
+    let acc = run(table, &repaired).map_err(|_| "Repaired program does not terminate")?;
+    Ok((repair, acc))
 }
 
 pub fn part2() -> i64 {
-    let mut instructions = load();
-    for i in 0usize..instructions.len() {
-        let ins = instructions[i];
-        match ins {
-            Instruction::JMP(value) => {
-                instructions[i] = Instruction::NOP(value);
-                if let Ok(i) = run(&instructions[..]) {
-                    return i;
-                }
-                instructions[i] = ins;
-            }
-            Instruction::NOP(value) => {
-                instructions[i] = Instruction::JMP(value);
-                if let Ok(i) = run(&instructions[..]) {
-                    return i;
-                }
-                instructions[i] = ins;
-            }
-            _ => {}
+    let table = OpcodeTable::handheld();
+    repair(&table, &load(&table)).unwrap().1
+}
+
+/// Runner command: debug the puzzle input, or the program in the given file.
+pub fn debug(args: &[String]) -> Result<(), String> {
+    let source = match args {
+        [] => include_str!("day8.txt").to_string(),
+        [path] => {
+            std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?
         }
-    }
-    panic!("no solution found")
+        _ => return Err("Usage: console [FILE]".to_string()),
+    };
+
+    let table = OpcodeTable::handheld();
+    let program = table.assemble(&source)?;
+    let mut machine = Machine::new(&table, &program);
+    console::debug(&mut machine, io::stdin().lock(), io::stdout()).map_err(|e| e.to_string())
 }
 
 #[test]
@@ -220,3 +264,80 @@ fn test_part1() {
 fn test_part2() {
     assert_eq!(part2(), 1319)
 }
+
+#[cfg(test)]
+static EXAMPLE: &str = "\
+nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6
+";
+
+#[test]
+fn test_reaches_end() {
+    let table = OpcodeTable::handheld();
+    let program = table.assemble(EXAMPLE).unwrap();
+    let reached = reaches_end(&table, &program);
+    assert_eq!(reached.iter().collect::<Vec<_>>(), &[8, 9]);
+}
+
+#[test]
+fn test_repair_example() {
+    let table = OpcodeTable::handheld();
+    let program = table.assemble(EXAMPLE).unwrap();
+    let nop = table.lookup("nop").unwrap();
+    assert_eq!(find_repairs(&table, &program), &[Repair { pc: 7, op: nop }]);
+    assert_eq!(repair(&table, &program), Ok((Repair { pc: 7, op: nop }, 8)));
+}
+
+#[test]
+fn test_repair_is_unique() {
+    let table = OpcodeTable::handheld();
+    let program = load(&table);
+    let repairs = find_repairs(&table, &program);
+    assert_eq!(repairs.len(), 1);
+
+    // Brute force: exactly the reported swap makes the program terminate.
+    let (jmp, nop) = (table.lookup("jmp").unwrap(), table.lookup("nop").unwrap());
+    let mut terminating = Vec::new();
+    for pc in 0..program.len() {
+        let mut swapped = program.clone();
+        swapped[pc].op = match program[pc].op {
+            op if op == jmp => nop,
+            op if op == nop => jmp,
+            _ => continue,
+        };
+        if run(&table, &swapped).is_ok() {
+            terminating.push(Repair {
+                pc,
+                op: swapped[pc].op,
+            });
+        }
+    }
+    assert_eq!(terminating, repairs);
+}
+
+#[test]
+fn test_repair_errors() {
+    let table = OpcodeTable::handheld();
+
+    let ambiguous = table.assemble("nop +2\njmp +0\n").unwrap();
+    assert_eq!(
+        repair(&table, &ambiguous),
+        Err("Ambiguous repair, candidates: 0, 1".to_string())
+    );
+
+    let terminates = table.assemble("acc +1\n").unwrap();
+    assert_eq!(
+        repair(&table, &terminates),
+        Err("No single instruction swap makes the program terminate".to_string())
+    );
+
+    let hopeless = table.assemble("acc +1\njmp -1\njmp -1\n").unwrap();
+    assert!(find_repairs(&table, &hopeless).is_empty());
+}
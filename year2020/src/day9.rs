@@ -18,21 +18,21 @@
 //! To be valid, the next number must be the sum of two of those numbers:
 //!
 //! - `26` would be a **valid** next number, as it could be `1` plus `25`
-//! (or many other pairs, like `2` and `24`).
+//!   (or many other pairs, like `2` and `24`).
 //! - `49` would be a **valid** next number, as it is the sum of `24` and `25`.
 //! - `100` would **not** be valid; no two of the previous `25` numbers sum to `100`.
 //! - `50` would also **not** be valid; although `25` appears in the previous `25` numbers,
-//! the two numbers in the pair must be different.
+//!   the two numbers in the pair must be different.
 //!
 //! Suppose the 26th number is `45`, and the first number (no longer an option, as it is more than
 //! 25 numbers ago) was `20`. Now, for the next number to be valid, there needs to be some pair of
 //! numbers among `1-19`, `21-25`, or `45` that add up to it:
 //!
 //! - `26` would still be a **valid** next number, as `1` and `25` are still within the previous
-//! 25 numbers.
+//!   25 numbers.
 //! - `65` would **not** be valid, as no two of the available numbers sum to it.
 //! - `64` and `66` would both be **valid**, as they are the result of `19+45` and `21+45`
-//! respectively.
+//!   respectively.
 //!
 //! Here is a larger example which only considers the previous **5** numbers(and has a preamble of
 //! length 5):
@@ -106,7 +106,10 @@
 //!
 //! **What is the encryption weakness in your XMAS-encrypted list of numbers?**
 //!
-use itertools::iproduct;
+use crate::ksum::{contiguous_sum, find_unsummable};
+
+/// Length of the preamble, and of the window of previous numbers each number is checked against.
+const PREAMBLE: usize = 25;
 
 fn load() -> Vec<i64> {
     include_str!("day9.txt")
@@ -115,36 +118,31 @@ fn load() -> Vec<i64> {
         .collect::<Vec<_>>()
 }
 
-pub fn part1() -> i64 {
-    load()
-        .windows(26)
-        .find_map(|w| {
-            let numbers = &w[0..25];
-            let sum = *w.last().unwrap();
+fn first_invalid(numbers: &[i64], preamble: usize) -> i64 {
+    numbers[find_unsummable(numbers, preamble).unwrap()]
+}
 
-            let is_valid_sum = iproduct!(numbers.iter(), numbers.iter())
-                .filter(|(&i, &j)| i != j)
-                .any(|(&i, &j)| i + j == sum);
+fn encryption_weakness(numbers: &[i64], preamble: usize) -> i64 {
+    let invalid = first_invalid(numbers, preamble);
+    let range = &numbers[contiguous_sum(numbers, invalid, 2).unwrap()];
+    range.iter().min().unwrap() + range.iter().max().unwrap()
+}
 
-            if is_valid_sum {
-                None
-            } else {
-                Some(sum)
-            }
-        })
-        .unwrap()
+pub fn part1() -> i64 {
+    first_invalid(&load(), PREAMBLE)
 }
 
 pub fn part2() -> i64 {
-    let part1 = part1();
-    let all = load();
-    (2usize..)
-        .find_map(|n| {
-            all.windows(n)
-                .find(|w| w.iter().sum::<i64>() == part1)
-                .map(|w| w.iter().min().unwrap() + w.iter().max().unwrap())
-        })
-        .unwrap()
+    encryption_weakness(&load(), PREAMBLE)
+}
+
+#[test]
+fn test_example() {
+    let numbers = [
+        35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309, 576,
+    ];
+    assert_eq!(first_invalid(&numbers, 5), 127);
+    assert_eq!(encryption_weakness(&numbers, 5), 62);
 }
 
 #[test]
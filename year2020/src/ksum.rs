@@ -0,0 +1,214 @@
+//! Searches for numbers adding up to a target, shared by day 1 and day 9.
+
+use std::ops::Range;
+
+use advent_of_code_core::hash::FxHashMap;
+
+/// Numbers at distinct positions that add up to the target, ordered by index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub indices: Vec<usize>,
+    pub values: Vec<i64>,
+}
+
+/// Find `k` numbers at distinct positions summing to `target`.
+///
+/// Pairs are found in a single pass with a hash map, larger `k` by fixing the smallest numbers
+/// of a sorted copy and closing with two pointers, in `O(n^(k - 1))`.
+pub fn k_sum(numbers: &[i64], k: usize, target: i64) -> Option<Match> {
+    let indices = match k {
+        2 => two_sum(numbers, target)?.to_vec(),
+        _ => {
+            let mut order = (0..numbers.len()).collect::<Vec<_>>();
+            order.sort_by_key(|&i| numbers[i]);
+            let sorted = order.iter().map(|&i| numbers[i]).collect::<Vec<_>>();
+
+            let mut indices = sorted_k_sum(&sorted, k, target)?
+                .into_iter()
+                .map(|i| order[i])
+                .collect::<Vec<_>>();
+            indices.sort_unstable();
+            indices
+        }
+    };
+
+    Some(Match {
+        values: indices.iter().map(|&i| numbers[i]).collect(),
+        indices,
+    })
+}
+
+fn two_sum(numbers: &[i64], target: i64) -> Option<[usize; 2]> {
+    let mut seen = FxHashMap::default();
+    for (j, &x) in numbers.iter().enumerate() {
+        if let Some(&i) = seen.get(&(target - x)) {
+            return Some([i, j]);
+        }
+        seen.entry(x).or_insert(j);
+    }
+    None
+}
+
+/// Positions in `sorted` of `k` numbers summing to `target`.
+fn sorted_k_sum(sorted: &[i64], k: usize, target: i64) -> Option<Vec<usize>> {
+    match k {
+        0 => (target == 0).then(Vec::new),
+        1 => sorted.binary_search(&target).ok().map(|i| vec![i]),
+        2 => {
+            let (mut lo, mut hi) = (0, sorted.len().checked_sub(1)?);
+            while lo < hi {
+                match (sorted[lo] + sorted[hi]).cmp(&target) {
+                    std::cmp::Ordering::Less => lo += 1,
+                    std::cmp::Ordering::Greater => hi -= 1,
+                    std::cmp::Ordering::Equal => return Some(vec![lo, hi]),
+                }
+            }
+            None
+        }
+        _ => (0..sorted.len())
+            // Equal numbers would only repeat the search of the first one.
+            .filter(|&i| i == 0 || sorted[i] != sorted[i - 1])
+            .find_map(|i| {
+                let rest = sorted_k_sum(&sorted[i + 1..], k - 1, target - sorted[i])?;
+                Some(
+                    std::iter::once(i)
+                        .chain(rest.into_iter().map(|j| i + 1 + j))
+                        .collect(),
+                )
+            }),
+    }
+}
+
+/// Find a run of at least `min_len` consecutive numbers summing to `target`, using a sliding
+/// window. All numbers must be non-negative.
+pub fn contiguous_sum(numbers: &[i64], target: i64, min_len: usize) -> Option<Range<usize>> {
+    let (mut start, mut sum) = (0, 0);
+    for (end, &x) in numbers.iter().enumerate() {
+        debug_assert!(x >= 0, "contiguous_sum requires non-negative numbers");
+        sum += x;
+        while sum > target && start <= end {
+            sum -= numbers[start];
+            start += 1;
+        }
+        if sum == target && end + 1 - start >= min_len {
+            return Some(start..end + 1);
+        }
+    }
+    None
+}
+
+/// Find the index of the first number that is not the sum of two different values among the
+/// `window` numbers before it.
+pub fn find_unsummable(numbers: &[i64], window: usize) -> Option<usize> {
+    let mut counts = FxHashMap::<i64, usize>::default();
+    for &x in numbers.iter().take(window) {
+        *counts.entry(x).or_default() += 1;
+    }
+
+    for i in window..numbers.len() {
+        let target = numbers[i];
+        let previous = &numbers[i - window..i];
+        let summable = previous
+            .iter()
+            .any(|&x| x * 2 != target && counts.contains_key(&(target - x)));
+        if !summable {
+            return Some(i);
+        }
+
+        let oldest = numbers[i - window];
+        match counts.get_mut(&oldest) {
+            Some(count) if *count > 1 => *count -= 1,
+            _ => {
+                counts.remove(&oldest);
+            }
+        }
+        *counts.entry(target).or_default() += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static EXPENSES: [i64; 6] = [1721, 979, 366, 299, 675, 1456];
+
+    static XMAS: [i64; 20] = [
+        35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309, 576,
+    ];
+
+    #[test]
+    fn test_k_sum() {
+        let pair = k_sum(&EXPENSES, 2, 2020).unwrap();
+        assert_eq!(pair.indices, &[0, 3]);
+        assert_eq!(pair.values, &[1721, 299]);
+
+        let triple = k_sum(&EXPENSES, 3, 2020).unwrap();
+        assert_eq!(triple.indices, &[1, 2, 4]);
+        assert_eq!(triple.values, &[979, 366, 675]);
+
+        assert_eq!(k_sum(&EXPENSES, 1, 366).unwrap().indices, &[2]);
+        assert_eq!(k_sum(&EXPENSES, 0, 0).unwrap().indices, &[] as &[usize]);
+        assert_eq!(k_sum(&EXPENSES, 2, 1), None);
+        assert_eq!(k_sum(&EXPENSES, 7, 1), None);
+        assert_eq!(k_sum(&[], 3, 0), None);
+    }
+
+    #[test]
+    fn test_k_sum_distinct_positions() {
+        // A number can only be used once, but equal numbers at different positions can pair up.
+        assert_eq!(k_sum(&[1010, 5], 2, 2020), None);
+        assert_eq!(k_sum(&[1010, 5, 1010], 2, 2020).unwrap().indices, &[0, 2]);
+        assert_eq!(k_sum(&[2, 7, 2, 2], 3, 6).unwrap().indices, &[0, 2, 3]);
+        assert_eq!(k_sum(&[2, 7, 2], 3, 6), None);
+    }
+
+    #[test]
+    fn test_k_sum_brute_force() {
+        let numbers = (0..40).map(|i| (i * 37 % 23) - 5).collect::<Vec<i64>>();
+        for k in 2..=4 {
+            for target in -10..40 {
+                let found = k_sum(&numbers, k, target);
+                if let Some(m) = &found {
+                    assert_eq!(m.values.iter().sum::<i64>(), target);
+                    assert_eq!(m.indices.len(), k);
+                    assert!(m.indices.windows(2).all(|w| w[0] < w[1]));
+                }
+
+                // A solution exists iff some k distinct positions sum to target.
+                let exists = (0..numbers.len())
+                    .flat_map(|i| (i + 1..numbers.len()).map(move |j| (i, j)))
+                    .any(|(i, j)| match k {
+                        2 => numbers[i] + numbers[j] == target,
+                        _ => k_sum(&numbers[j + 1..], k - 2, target - numbers[i] - numbers[j])
+                            .is_some(),
+                    });
+                assert_eq!(found.is_some(), exists, "k = {}, target = {}", k, target);
+            }
+        }
+    }
+
+    #[test]
+    fn test_contiguous_sum() {
+        assert_eq!(contiguous_sum(&XMAS, 127, 2), Some(2..6));
+        assert_eq!(&XMAS[2..6], &[15, 25, 47, 40]);
+        assert_eq!(contiguous_sum(&XMAS, 127, 1), Some(2..6));
+        assert_eq!(contiguous_sum(&XMAS, 576, 1), Some(11..15));
+        assert_eq!(contiguous_sum(&[1, 2, 10, 3], 10, 1), Some(2..3));
+        assert_eq!(contiguous_sum(&[1, 2, 10, 3], 10, 2), None);
+        assert_eq!(contiguous_sum(&[5, 0, 3], 5, 2), Some(0..2));
+        assert_eq!(contiguous_sum(&[], 0, 1), None);
+    }
+
+    #[test]
+    fn test_find_unsummable() {
+        assert_eq!(find_unsummable(&XMAS, 5), Some(14));
+        assert_eq!(XMAS[14], 127);
+        assert_eq!(find_unsummable(&XMAS, 20), None);
+        assert_eq!(find_unsummable(&XMAS, 1), Some(1));
+
+        // The two numbers must have different values.
+        assert_eq!(find_unsummable(&[1, 3, 3, 6], 3), Some(3));
+        assert_eq!(find_unsummable(&[1, 5, 3, 6], 3), None);
+    }
+}
@@ -1,3 +1,4 @@
+mod console;
 mod day1;
 mod day10;
 mod day12;
@@ -8,8 +9,9 @@ mod day5;
 mod day6;
 mod day8;
 mod day9;
+mod ksum;
 
-use advent_of_code_core::{menu, Year};
+use advent_of_code_core::{menu_with_commands, Command, Year};
 
 pub static SOLUTIONS: Year = [
     (Some(day1::part1), Some(day1::part2)),
@@ -23,7 +25,7 @@ pub static SOLUTIONS: Year = [
     (Some(day9::part1), Some(day9::part2)),
     (Some(day10::part1), Some(day10::part2)),
     (None, None),
-    (Some(day12::part1), None),
+    (Some(day12::part1), Some(day12::part2)),
     (None, None),
     (None, None),
     (None, None),
@@ -38,6 +40,42 @@ pub static SOLUTIONS: Year = [
     (None, None),
 ];
 
+advent_of_code_core::sources!(SOURCES, SOLUTIONS, [1, 2, 3, 4, 5, 6, 8, 9, 10, 12]);
+
+static COMMANDS: [Command; 6] = [
+    (
+        "console",
+        "[FILE]  debug the day 8 handheld console program",
+        day8::debug,
+    ),
+    (
+        "passwords",
+        "[--positions] [FILE]  list the day 2 passwords failing their policy",
+        day2::report,
+    ),
+    (
+        "slopes",
+        "[--wrap] [MAX | RIGHT[/PER],DOWN...]  count the day 3 trees hit on each slope",
+        day3::report,
+    ),
+    (
+        "passports",
+        "[SCHEMA]  list the violations of each invalid day 4 passport",
+        day4::report,
+    ),
+    (
+        "seats",
+        "[--rows=BITS] [--columns=BITS] [--letters=FBLR] [FILE]  show the day 5 seat map, \
+         occupancy and free seats",
+        day5::report,
+    ),
+    (
+        "ferry",
+        "[FILE]  trace the day 12 ship and waypoint positions",
+        day12::report,
+    ),
+];
+
 fn main() -> Result<(), String> {
-    menu(&SOLUTIONS)
+    menu_with_commands(&SOLUTIONS, &SOURCES, &COMMANDS)
 }
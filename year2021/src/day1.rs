@@ -1,18 +1,20 @@
-fn load() -> Vec<i16> {
+//! # Day 1: Sonar Sweep
+
+use crate::window::summarize;
+
+/// The depth readings, parsed as they are read.
+fn load() -> impl Iterator<Item = i64> {
     include_str!("day1.txt")
         .lines()
-        .map(|s| s.parse::<i16>().unwrap())
-        .collect::<Vec<_>>()
+        .map(|s| s.parse::<i64>().unwrap())
 }
 
 pub fn part1() -> i64 {
-    load().windows(2).filter(|w| w[1] > w[0]).count() as i64
+    summarize(load(), 1).increases as i64
 }
 
 pub fn part2() -> i64 {
-    let sums: Vec<i16> = load().windows(3).map(|w| w.iter().sum()).collect();
-
-    sums.windows(2).filter(|w| w[1] > w[0]).count() as i64
+    summarize(load(), 3).increases as i64
 }
 
 #[test]
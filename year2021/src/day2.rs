@@ -1,61 +1,149 @@
+//! # Day 2: Dive!
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Action {
-    FORWARD,
-    UP,
-    DOWN,
+    Forward,
+    Up,
+    Down,
+}
+
+/// The position of the submarine. `aim` is only used by navigation modes that steer with it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Submarine {
+    x: i64,
+    depth: i64,
+    aim: i64,
+}
+
+/// How a navigation mode moves the submarine for an action and its argument.
+type Navigate = fn(&mut Submarine, Action, i64);
+
+/// The navigation mode of part 1, where up and down change the depth.
+fn plain(sub: &mut Submarine, action: Action, arg: i64) {
+    match action {
+        Action::Forward => sub.x += arg,
+        Action::Up => sub.depth -= arg,
+        Action::Down => sub.depth += arg,
+    }
+}
+
+/// The navigation mode of part 2, where up and down turn the submarine and moving forward changes
+/// the depth.
+fn aim(sub: &mut Submarine, action: Action, arg: i64) {
+    match action {
+        Action::Forward => {
+            sub.x += arg;
+            sub.depth += sub.aim * arg;
+        }
+        Action::Up => sub.aim -= arg,
+        Action::Down => sub.aim += arg,
+    }
 }
 
-fn parse(s: &str) -> (Action, i64) {
+fn parse_line(s: &str) -> Result<(Action, i64), String> {
     let mut split = s.split_whitespace();
     let op = match split.next() {
-        Some("forward") => Action::FORWARD,
-        Some("up") => Action::UP,
-        Some("down") => Action::DOWN,
-        Some(s) => panic!("unknown action '{}'", s),
-        None => panic!("No action"),
+        Some("forward") => Action::Forward,
+        Some("up") => Action::Up,
+        Some("down") => Action::Down,
+        Some(s) => return Err(format!("unknown action '{}'", s)),
+        None => return Err("No action".to_string()),
     };
 
-    let arg = split.next().unwrap().parse::<i64>().unwrap();
+    let arg = split
+        .next()
+        .ok_or("Missing argument")?
+        .parse::<i64>()
+        .map_err(|e| format!("Invalid argument: {}", e))?;
 
-    (op, arg)
+    match split.next() {
+        Some(extra) => Err(format!("Unexpected '{}'", extra)),
+        None => Ok((op, arg)),
+    }
 }
 
-fn load() -> Vec<(Action, i64)> {
-    include_str!("day2.txt")
+fn parse(input: &str) -> Result<Vec<(Action, i64)>, String> {
+    input
         .lines()
-        .map(|s| parse(s))
-        .collect::<Vec<_>>()
+        .enumerate()
+        .map(|(i, line)| parse_line(line).map_err(|e| format!("Line {}: {}", i + 1, e)))
+        .collect()
 }
 
-pub fn part1() -> i64 {
-    let mut x = 0;
-    let mut y = 0;
-    for (op, arg) in load() {
-        match op {
-            Action::FORWARD => x += arg,
-            Action::UP => y -= arg,
-            Action::DOWN => y += arg,
-        }
+fn load() -> Vec<(Action, i64)> {
+    parse(include_str!("day2.txt")).unwrap()
+}
+
+/// Every position of the submarine, from the start to after the last command.
+fn trajectory(commands: &[(Action, i64)], navigate: Navigate) -> Vec<Submarine> {
+    let mut sub = Submarine::default();
+    let mut trajectory = vec![sub];
+    for &(action, arg) in commands {
+        navigate(&mut sub, action, arg);
+        trajectory.push(sub);
     }
-    x * y
+    trajectory
+}
+
+fn solve(navigate: Navigate) -> i64 {
+    let sub = *trajectory(&load(), navigate).last().unwrap();
+    sub.x * sub.depth
+}
+
+pub fn part1() -> i64 {
+    solve(plain)
 }
 
 pub fn part2() -> i64 {
-    let mut x = 0;
-    let mut y = 0;
-    let mut aim = 0;
-    for (op, arg) in load() {
-        match op {
-            Action::FORWARD => {
-                x += arg;
-                y += aim * arg;
-            }
-            Action::UP => aim -= arg,
-            Action::DOWN => aim += arg,
-        }
+    solve(aim)
+}
+
+/// Replay the commands of the puzzle input or `FILE`, printing the trajectory of the submarine
+/// with the navigation mode given by `--mode=NAME` (default `plain`).
+pub fn replay(args: &[String]) -> Result<(), String> {
+    let usage = || "Usage: dive [--mode=plain|aim] [FILE]".to_string();
+
+    let (mode, args) = match args {
+        [flag, rest @ ..] if flag.starts_with("--mode=") => (&flag["--mode=".len()..], rest),
+        _ => ("plain", args),
+    };
+    let navigate: Navigate = match mode {
+        "plain" => plain,
+        "aim" => aim,
+        _ => return Err(usage()),
+    };
+    let commands = match args {
+        [] => load(),
+        [file] => parse(&std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?)?,
+        _ => return Err(usage()),
+    };
+
+    let trajectory = trajectory(&commands, navigate);
+    println!("{:<12} {:>8} {:>8} {:>8}", "", "x", "depth", "aim");
+    for (i, sub) in trajectory.iter().enumerate() {
+        let command = match i {
+            0 => String::new(),
+            _ => format!("{:?} {}", commands[i - 1].0, commands[i - 1].1),
+        };
+        println!(
+            "{:<12} {:>8} {:>8} {:>8}",
+            command, sub.x, sub.depth, sub.aim
+        );
     }
-    x * y
+    let end = trajectory[trajectory.len() - 1];
+    println!("x * depth = {}", end.x * end.depth);
+    Ok(())
 }
 
+#[cfg(test)]
+static EXAMPLE: &str = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+";
+
 #[test]
 fn test_part1() {
     assert_eq!(part1(), 1692075);
@@ -65,3 +153,59 @@ fn test_part1() {
 fn test_part2() {
     assert_eq!(part2(), 1749524700);
 }
+
+#[test]
+fn test_example() {
+    let commands = parse(EXAMPLE).unwrap();
+    let end = |navigate| *trajectory(&commands, navigate).last().unwrap();
+    assert_eq!((end(plain).x, end(plain).depth), (15, 10));
+    assert_eq!((end(aim).x, end(aim).depth), (15, 60));
+}
+
+#[test]
+fn test_trajectory() {
+    let commands = parse(EXAMPLE).unwrap();
+    let positions = trajectory(&commands, aim)
+        .iter()
+        .map(|s| (s.x, s.depth, s.aim))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        positions,
+        [
+            (0, 0, 0),
+            (5, 0, 0),
+            (5, 0, 5),
+            (13, 40, 5),
+            (13, 40, 2),
+            (13, 40, 10),
+            (15, 60, 10),
+        ]
+    );
+}
+
+#[test]
+fn test_other_mode() {
+    // Up and down swapped, and forward moving twice as far.
+    let inverted: Navigate = |sub, action, arg| match action {
+        Action::Forward => sub.x += 2 * arg,
+        Action::Up => sub.depth += arg,
+        Action::Down => sub.depth -= arg,
+    };
+    let commands = parse(EXAMPLE).unwrap();
+    let end = *trajectory(&commands, inverted).last().unwrap();
+    assert_eq!((end.x, end.depth), (30, -10));
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        parse("forward 1\nbackward 2").unwrap_err(),
+        "Line 2: unknown action 'backward'"
+    );
+    assert_eq!(parse("up").unwrap_err(), "Line 1: Missing argument");
+    assert_eq!(parse("down 1 2").unwrap_err(), "Line 1: Unexpected '2'");
+    assert!(parse("up x")
+        .unwrap_err()
+        .starts_with("Line 1: Invalid argument: "));
+    assert_eq!(parse("\n").unwrap_err(), "Line 1: No action");
+}
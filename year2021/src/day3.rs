@@ -1,5 +1,22 @@
+//! # Day 3: Binary Diagnostic
+
+/// The numbers of the diagnostic report, and the number of bits they were written with.
+struct Report {
+    width: usize,
+    numbers: Vec<u32>,
+}
+
+/// Which bit value the life support ratings keep at each position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Criteria {
+    /// Keep the most common value, `1` on a tie.
+    MostCommon,
+    /// Keep the least common value, `0` on a tie.
+    LeastCommon,
+}
+
 fn parse(s: &str) -> u32 {
-    let mut u = 32;
+    let mut u = 0;
     for (i, c) in s.chars().rev().enumerate() {
         match c {
             '0' => {}
@@ -10,11 +27,23 @@ fn parse(s: &str) -> u32 {
     u
 }
 
-fn load() -> Vec<u32> {
-    include_str!("day3.txt")
+/// Parse a report, taking the bit width from the lines, which must all be equally long.
+fn parse_report(input: &str) -> Report {
+    let width = input.lines().next().map_or(0, str::len);
+    assert!(width <= 32, "numbers of {} bits do not fit a u32", width);
+
+    let numbers = input
         .lines()
-        .map(|s| parse(s))
-        .collect::<Vec<_>>()
+        .map(|l| {
+            assert_eq!(l.len(), width, "line '{}' is not {} bits wide", l, width);
+            parse(l)
+        })
+        .collect::<Vec<_>>();
+    Report { width, numbers }
+}
+
+fn load() -> Report {
+    parse_report(include_str!("day3.txt"))
 }
 
 fn count_number_of_set_bits(numbers: &[u32], bit_idx: usize) -> usize {
@@ -26,40 +55,134 @@ fn count_number_of_set_bits(numbers: &[u32], bit_idx: usize) -> usize {
     count
 }
 
-pub fn part1() -> i64 {
-    let numbers = load();
-
-    let gamma = (0usize..12)
-        .map(|i| {
-            (
-                i,
-                count_number_of_set_bits(&numbers, i) >= numbers.len() / 2,
-            )
-        })
-        .fold(0u32, |acc, (i, bit)| acc | ((bit as u32) << i)) as i64;
-
-    let epsilon = (0usize..12)
-        .map(|i| {
-            (
-                i,
-                count_number_of_set_bits(&numbers, i) <= numbers.len() / 2,
-            )
-        })
-        .fold(0u32, |acc, (i, bit)| acc | ((bit as u32) << i)) as i64;
+/// Whether `1` is at least as common as `0` at `bit_idx`.
+fn ones_most_common(numbers: &[u32], bit_idx: usize) -> bool {
+    count_number_of_set_bits(numbers, bit_idx) * 2 >= numbers.len()
+}
+
+/// Filter `numbers` from the most significant of `width` bits down, keeping only the numbers
+/// whose bit matches the `criteria` among the numbers left, until one number remains.
+fn filter_by_bit_criteria(numbers: &[u32], width: usize, criteria: Criteria) -> Option<u32> {
+    let mut left = numbers.to_vec();
+    for bit_idx in (0..width).rev() {
+        if left.len() <= 1 {
+            break;
+        }
+        let keep = ones_most_common(&left, bit_idx) == (criteria == Criteria::MostCommon);
+        left.retain(|n| (n >> bit_idx & 1 == 1) == keep);
+    }
+    match left[..] {
+        [n] => Some(n),
+        _ => None,
+    }
+}
+
+/// The gamma rate multiplied by the epsilon rate.
+fn power_consumption(report: &Report) -> u64 {
+    let gamma = (0..report.width)
+        .filter(|&i| ones_most_common(&report.numbers, i))
+        .fold(0u32, |acc, i| acc | 1 << i);
+    let mask = ((1u64 << report.width) - 1) as u32;
+    let epsilon = !gamma & mask;
+    gamma as u64 * epsilon as u64
+}
 
-    gamma * epsilon
+/// The oxygen generator rating multiplied by the CO2 scrubber rating.
+fn life_support_rating(report: &Report) -> u64 {
+    let rating =
+        |criteria| filter_by_bit_criteria(&report.numbers, report.width, criteria).unwrap();
+    rating(Criteria::MostCommon) as u64 * rating(Criteria::LeastCommon) as u64
+}
+
+pub fn part1() -> i64 {
+    power_consumption(&load()) as i64
 }
 
 pub fn part2() -> i64 {
-    0
+    life_support_rating(&load()) as i64
 }
 
+#[cfg(test)]
+static EXAMPLE: &str = "00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010
+";
+
 #[test]
 fn test_parse() {
     assert_eq!(parse("100100111101"), 2365);
+    assert_eq!(parse("000000000000"), 0);
 }
 
 #[test]
 fn test_part1() {
     assert_eq!(part1(), 3009600);
 }
+
+#[test]
+fn test_part2() {
+    assert_eq!(part2(), 6940518);
+}
+
+#[test]
+fn test_example() {
+    let report = parse_report(EXAMPLE);
+    assert_eq!(report.width, 5);
+    assert_eq!(power_consumption(&report), 198);
+
+    let numbers = &report.numbers;
+    assert_eq!(
+        filter_by_bit_criteria(numbers, 5, Criteria::MostCommon),
+        Some(0b10111)
+    );
+    assert_eq!(
+        filter_by_bit_criteria(numbers, 5, Criteria::LeastCommon),
+        Some(0b01010)
+    );
+    assert_eq!(life_support_rating(&report), 230);
+}
+
+#[test]
+fn test_filter_by_bit_criteria() {
+    // Ties keep 1 for the most common and 0 for the least common value.
+    assert_eq!(
+        filter_by_bit_criteria(&[0b01, 0b10], 2, Criteria::MostCommon),
+        Some(0b10)
+    );
+    assert_eq!(
+        filter_by_bit_criteria(&[0b01, 0b10], 2, Criteria::LeastCommon),
+        Some(0b01)
+    );
+    // Duplicates can not be told apart by any bit.
+    assert_eq!(
+        filter_by_bit_criteria(&[3, 3], 2, Criteria::MostCommon),
+        None
+    );
+    assert_eq!(filter_by_bit_criteria(&[], 2, Criteria::MostCommon), None);
+    assert_eq!(
+        filter_by_bit_criteria(&[7], 3, Criteria::LeastCommon),
+        Some(7)
+    );
+}
+
+#[test]
+fn test_width_detection() {
+    let report = parse_report("1\n0\n1\n");
+    assert_eq!(report.width, 1);
+    assert_eq!(power_consumption(&report), 0);
+
+    let wide = "1".repeat(32) + "\n" + &"0".repeat(32);
+    let report = parse_report(&wide);
+    assert_eq!(report.width, 32);
+    assert_eq!(report.numbers, [u32::MAX, 0]);
+    assert_eq!(power_consumption(&report), 0);
+}
@@ -1,8 +1,9 @@
 mod day1;
 mod day2;
 mod day3;
+mod window;
 
-use advent_of_code_core::{menu, Year};
+use advent_of_code_core::{menu_with_commands, Command, Year};
 
 pub static SOLUTIONS: Year = [
     (Some(day1::part1), Some(day1::part2)),
@@ -31,6 +32,14 @@ pub static SOLUTIONS: Year = [
     (None, None),
 ];
 
+advent_of_code_core::sources!(SOURCES, SOLUTIONS, [1, 2, 3]);
+
+static COMMANDS: [Command; 1] = [(
+    "dive",
+    "[--mode=plain|aim] [FILE]  replay day 2 commands, printing the submarine trajectory",
+    day2::replay,
+)];
+
 fn main() -> Result<(), String> {
-    menu(&SOLUTIONS)
+    menu_with_commands(&SOLUTIONS, &SOURCES, &COMMANDS)
 }
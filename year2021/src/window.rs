@@ -0,0 +1,150 @@
+//! Changes between the sums of consecutive sliding windows over a stream of readings.
+
+use std::collections::VecDeque;
+
+/// How the sum of a window compares to the sum of the window before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Increase,
+    Decrease,
+    Plateau,
+}
+
+/// The number of windows of each kind of [`Change`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub increases: usize,
+    pub decreases: usize,
+    pub plateaus: usize,
+}
+
+/// An iterator over the start position of each window after the first, and how its sum changed.
+///
+/// Only the last `size` readings are kept, so the readings can be streamed from any source.
+/// Consecutive windows share all but one reading, so their sums differ by the reading entering
+/// the window minus the reading leaving it.
+pub struct WindowChanges<I> {
+    readings: I,
+    window: VecDeque<i64>,
+    size: usize,
+    position: usize,
+}
+
+impl<I: Iterator<Item = i64>> Iterator for WindowChanges<I> {
+    type Item = (usize, Change);
+
+    fn next(&mut self) -> Option<(usize, Change)> {
+        while self.window.len() < self.size {
+            self.window.push_back(self.readings.next()?);
+        }
+
+        let entering = self.readings.next()?;
+        let leaving = self.window.pop_front().unwrap();
+        self.window.push_back(entering);
+        self.position += 1;
+
+        let change = match entering.cmp(&leaving) {
+            std::cmp::Ordering::Greater => Change::Increase,
+            std::cmp::Ordering::Less => Change::Decrease,
+            std::cmp::Ordering::Equal => Change::Plateau,
+        };
+        Some((self.position, change))
+    }
+}
+
+/// Compare the sums of consecutive windows of `size` readings.
+pub fn window_changes<I: IntoIterator<Item = i64>>(
+    readings: I,
+    size: usize,
+) -> WindowChanges<I::IntoIter> {
+    assert!(size > 0, "windows must hold at least one reading");
+    WindowChanges {
+        readings: readings.into_iter(),
+        window: VecDeque::with_capacity(size),
+        size,
+        position: 0,
+    }
+}
+
+/// Count the changes between consecutive windows of `size` readings.
+pub fn summarize<I: IntoIterator<Item = i64>>(readings: I, size: usize) -> Summary {
+    let mut summary = Summary::default();
+    for (_, change) in window_changes(readings, size) {
+        match change {
+            Change::Increase => summary.increases += 1,
+            Change::Decrease => summary.decreases += 1,
+            Change::Plateau => summary.plateaus += 1,
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static DEPTHS: [i64; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    fn positions(readings: &[i64], size: usize, change: Change) -> Vec<usize> {
+        window_changes(readings.iter().copied(), size)
+            .filter(|&(_, c)| c == change)
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    #[test]
+    fn test_summarize() {
+        let single = summarize(DEPTHS, 1);
+        assert_eq!(single.increases, 7);
+        assert_eq!(single.decreases, 2);
+        assert_eq!(single.plateaus, 0);
+
+        let triple = summarize(DEPTHS, 3);
+        assert_eq!(
+            triple,
+            Summary {
+                increases: 5,
+                decreases: 1,
+                plateaus: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_positions() {
+        assert_eq!(positions(&DEPTHS, 1, Change::Decrease), &[4, 8]);
+        assert_eq!(positions(&DEPTHS, 3, Change::Plateau), &[2]);
+        assert_eq!(positions(&DEPTHS, 3, Change::Decrease), &[3]);
+
+        // The positions agree with summing every window.
+        for size in 1..=DEPTHS.len() {
+            let sums = DEPTHS
+                .windows(size)
+                .map(|w| w.iter().sum::<i64>())
+                .collect::<Vec<_>>();
+            let expected = (1..sums.len())
+                .filter(|&i| sums[i] > sums[i - 1])
+                .collect::<Vec<_>>();
+            assert_eq!(positions(&DEPTHS, size, Change::Increase), expected);
+        }
+    }
+
+    #[test]
+    fn test_short_and_streamed() {
+        assert_eq!(summarize([], 1), Summary::default());
+        assert_eq!(summarize([1, 2, 3], 3), Summary::default());
+        assert_eq!(summarize([1, 2, 3], 4), Summary::default());
+
+        // Readings are pulled lazily, one window ahead.
+        let mut changes = window_changes((0..).map(|i| i % 3), 2);
+        assert_eq!(changes.next(), Some((1, Change::Increase)));
+        assert_eq!(changes.next(), Some((2, Change::Decrease)));
+        assert_eq!(changes.nth(2999), Some((3002, Change::Decrease)));
+    }
+
+    #[test]
+    #[should_panic(expected = "windows must hold at least one reading")]
+    fn test_empty_window() {
+        window_changes([1, 2], 0);
+    }
+}
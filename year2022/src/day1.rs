@@ -1,37 +1,131 @@
-fn load() -> Vec<Vec<i64>> {
-    let mut all = Vec::new();
-    let mut current = Vec::new();
-
-    for line in include_str!("day1.txt").lines() {
-        if line.is_empty() {
-            all.push(std::mem::replace(&mut current, Vec::new()));
-        } else {
-            current.push(line.parse::<i64>().unwrap())
+//! # Day 1: Calorie Counting
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+/// The `k` largest totals seen so far with the index of the elf carrying them, largest first.
+/// Earlier elves come first on a tie.
+struct TopK {
+    k: usize,
+    best: Vec<(usize, i64)>,
+}
+
+impl TopK {
+    fn new(k: usize) -> TopK {
+        TopK {
+            k,
+            best: Vec::new(),
         }
     }
 
-    all
+    fn push(&mut self, elf: usize, total: i64) {
+        let at = self.best.partition_point(|&(_, t)| t >= total);
+        if at < self.k {
+            self.best.insert(at, (elf, total));
+            self.best.truncate(self.k);
+        }
+    }
 }
 
-pub fn part1() -> i64 {
-    load()
-        .into_iter()
-        .map(|elf| elf.into_iter().sum::<i64>())
-        .max()
+/// Sum each group of lines separated by blank lines, as the lines are read. Several blank lines
+/// in a row separate a single pair of groups.
+fn group_totals(
+    lines: impl Iterator<Item = io::Result<String>>,
+) -> impl Iterator<Item = Result<i64, String>> {
+    let mut lines = lines.enumerate().peekable();
+    std::iter::from_fn(move || {
+        while lines
+            .next_if(|(_, line)| line.as_ref().is_ok_and(|line| line.is_empty()))
+            .is_some()
+        {}
+        lines.peek()?;
+
+        let mut total = 0;
+        while let Some((i, line)) =
+            lines.next_if(|(_, line)| !line.as_ref().is_ok_and(|line| line.is_empty()))
+        {
+            let calories = line
+                .map_err(|e| e.to_string())
+                .and_then(|line| line.parse::<i64>().map_err(|e| e.to_string()));
+            match calories {
+                Ok(calories) => total += calories,
+                Err(e) => return Some(Err(format!("Line {}: {}", i + 1, e))),
+            }
+        }
+        Some(Ok(total))
+    })
+}
+
+/// The `k` elves carrying the most calories, as `(elf, total)` with elves numbered from 0.
+fn top_k(input: impl BufRead, k: usize) -> Result<Vec<(usize, i64)>, String> {
+    let mut top = TopK::new(k);
+    for (elf, total) in group_totals(input.lines()).enumerate() {
+        top.push(elf, total?);
+    }
+    Ok(top.best)
+}
+
+fn sum_top(k: usize) -> i64 {
+    top_k(include_str!("day1.txt").as_bytes(), k)
         .unwrap()
+        .iter()
+        .map(|&(_, total)| total)
+        .sum()
+}
+
+pub fn part1() -> i64 {
+    sum_top(1)
 }
 
 pub fn part2() -> i64 {
-    let mut all = load()
-        .into_iter()
-        .map(|elf| elf.into_iter().sum::<i64>())
-        .collect::<Vec<_>>();
+    sum_top(3)
+}
 
-    all.sort_by(|a, b| a.cmp(b).reverse());
+/// List the `K` (default 3) elves of the puzzle input or `FILE` carrying the most calories.
+pub fn report(args: &[String]) -> Result<(), String> {
+    let parse_k = |k: &str| k.parse::<usize>().map_err(|e| format!("Invalid K: {}", e));
+    let puzzle_input = || Box::new(include_str!("day1.txt").as_bytes()) as Box<dyn BufRead>;
+    let (k, input) = match args {
+        [] => (3, puzzle_input()),
+        [k] => (parse_k(k)?, puzzle_input()),
+        [k, file] => {
+            let file = File::open(file).map_err(|e| format!("{}: {}", file, e))?;
+            (
+                parse_k(k)?,
+                Box::new(BufReader::new(file)) as Box<dyn BufRead>,
+            )
+        }
+        _ => return Err("Usage: calories [K] [FILE]".to_string()),
+    };
 
-    all[0..3].iter().sum::<i64>()
+    let top = top_k(input, k)?;
+    for (rank, (elf, total)) in top.iter().enumerate() {
+        println!("{:>4}. Elf {:<6} {:>8}", rank + 1, elf + 1, total);
+    }
+    println!(
+        "Total: {}",
+        top.iter().map(|&(_, total)| total).sum::<i64>()
+    );
+    Ok(())
 }
 
+#[cfg(test)]
+static EXAMPLE: &str = "1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000
+";
+
 #[test]
 fn test_part1() {
     assert_eq!(part1(), 69693);
@@ -41,3 +135,44 @@ fn test_part1() {
 fn test_part2() {
     assert_eq!(part2(), 200945);
 }
+
+#[test]
+fn test_example() {
+    assert_eq!(top_k(EXAMPLE.as_bytes(), 1), Ok(vec![(3, 24000)]));
+    assert_eq!(
+        top_k(EXAMPLE.as_bytes(), 3),
+        Ok(vec![(3, 24000), (2, 11000), (4, 10000)])
+    );
+    assert_eq!(top_k(EXAMPLE.as_bytes(), 10).unwrap().len(), 5);
+    assert_eq!(top_k(EXAMPLE.as_bytes(), 0), Ok(vec![]));
+}
+
+#[test]
+fn test_group_totals() {
+    let totals =
+        |input: &str| group_totals(input.as_bytes().lines()).collect::<Result<Vec<_>, _>>();
+    assert_eq!(totals("1\n2\n\n3"), Ok(vec![3, 3]));
+    assert_eq!(totals("\n\n1\n\n\n\n2\n\n"), Ok(vec![1, 2]));
+    assert_eq!(totals(""), Ok(vec![]));
+    assert!(totals("1\n\nx\n").unwrap_err().starts_with("Line 3: "));
+
+    // Read errors are reported with their line like parse errors.
+    let invalid = group_totals(b"1\n\xff\n".lines()).collect::<Result<Vec<_>, _>>();
+    assert!(invalid.unwrap_err().starts_with("Line 2: "));
+}
+
+#[test]
+fn test_top_k_ties_and_order() {
+    let mut top = TopK::new(2);
+    for (elf, total) in [5, 7, 5, 7, 1, 9].into_iter().enumerate() {
+        top.push(elf, total);
+        assert!(top.best.len() <= 2);
+    }
+    assert_eq!(top.best, [(5, 9), (1, 7)]);
+
+    let mut top = TopK::new(3);
+    for (elf, total) in [4, 4, 4, 4].into_iter().enumerate() {
+        top.push(elf, total);
+    }
+    assert_eq!(top.best, [(0, 4), (1, 4), (2, 4)]);
+}
@@ -1,74 +1,250 @@
-fn score_part1(opponent_hand: char, my_hand: char) -> i64 {
-    let score_hand = match my_hand {
-        'X' => 1,
-        'Y' => 2,
-        'Z' => 3,
-        _ => panic!("Unexpected hand '{}'", my_hand),
-    };
-    let score_winner = match (my_hand, opponent_hand) {
-        ('X', 'A') => 3,
-        ('Y', 'B') => 3,
-        ('Z', 'C') => 3,
-        ('X', 'B') => 0,
-        ('Y', 'C') => 0,
-        ('Z', 'A') => 0,
-        ('X', 'C') => 6,
-        ('Y', 'A') => 6,
-        ('Z', 'B') => 6,
-        _ => panic!("Unexpected hands '{}' '{}'", my_hand, opponent_hand),
-    };
-    score_hand + score_winner
-}
-
-fn score_part2(opponent_hand: char, outcome: char) -> i64 {
-    let my_hand = match (opponent_hand, outcome) {
-        ('A', 'Y') => 'X',
-        ('B', 'Y') => 'Y',
-        ('C', 'Y') => 'Z',
-        ('A', 'X') => 'Z',
-        ('B', 'X') => 'X',
-        ('C', 'X') => 'Y',
-        ('A', 'Z') => 'Y',
-        ('B', 'Z') => 'Z',
-        ('C', 'Z') => 'X',
-        _ => panic!(
-            "Unexpected hand or outcome '{}' '{}'",
-            opponent_hand, outcome
-        ),
-    };
+//! # Day 2: Rock Paper Scissors
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+/// What the second column of the strategy guide means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Shape,
+    Outcome,
+}
+
+/// The rules of a game, how its strategy guide is written, and how rounds are scored.
+///
+/// Shapes are numbered `0..shapes` in the order of their cyclic "beats" relation: each shape beats
+/// the shapes an odd number of steps before it, which is only consistent for an odd count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    shapes: usize,
+    /// The score for playing each shape.
+    shape_scores: Vec<i64>,
+    /// The score for a loss, a draw and a win.
+    outcome_scores: [i64; 3],
+    /// The symbol for each shape in the opponent's column.
+    opponent: Vec<char>,
+    /// The symbol for each shape in the second column when it is a response.
+    response: Vec<char>,
+    /// The symbol for a loss, a draw and a win in the second column when it is an outcome.
+    outcomes: Vec<char>,
+}
 
-    score_part1(opponent_hand, my_hand)
+impl Outcome {
+    const ALL: [Outcome; 3] = [Outcome::Loss, Outcome::Draw, Outcome::Win];
 }
 
-fn load() -> Vec<(char, char)> {
-    let mut res = Vec::new();
+impl Game {
+    /// A game with one shape per entry of `shape_scores`, and the symbols of the strategy guide
+    /// given as strings with one character per shape, or per outcome.
+    pub fn new(
+        shape_scores: Vec<i64>,
+        outcome_scores: [i64; 3],
+        opponent: &str,
+        response: &str,
+        outcomes: &str,
+    ) -> Result<Game, String> {
+        let shapes = shape_scores.len();
+        if shapes < 3 || shapes.is_multiple_of(2) {
+            return Err(format!(
+                "A game needs an odd number of at least 3 shapes, got {}",
+                shapes
+            ));
+        }
+
+        let symbols = |name: &str, s: &str, count: usize| {
+            let symbols = s.chars().collect::<Vec<_>>();
+            if symbols.len() != count {
+                return Err(format!("Expected {} {} symbols, got '{}'", count, name, s));
+            }
+            for (i, &c) in symbols.iter().enumerate() {
+                if c.is_whitespace() {
+                    return Err(format!("Invalid {} symbol '{}'", name, c));
+                }
+                if symbols[..i].contains(&c) {
+                    return Err(format!("Duplicate {} symbol '{}'", name, c));
+                }
+            }
+            Ok(symbols)
+        };
+
+        Ok(Game {
+            shapes,
+            opponent: symbols("opponent", opponent, shapes)?,
+            response: symbols("response", response, shapes)?,
+            outcomes: symbols("outcome", outcomes, 3)?,
+            shape_scores,
+            outcome_scores,
+        })
+    }
+
+    /// Rock-Paper-Scissors as in the puzzle: `A`, `B`, `C` for the opponent, `X`, `Y`, `Z` for
+    /// either the response or the outcome, 1 to 3 points per shape and 0, 3 or 6 per outcome.
+    pub fn classic() -> Game {
+        Game::new(vec![1, 2, 3], [0, 3, 6], "ABC", "XYZ", "XYZ").unwrap()
+    }
 
-    for line in include_str!("day2.txt").lines() {
-        let mut hand_iter = line.split_whitespace().flat_map(|c| c.chars());
+    /// Rock-Paper-Scissors-Spock-Lizard, with `A` to `E` and `V` to `Z` for the shapes in that
+    /// order and `X`, `Y`, `Z` for the outcomes.
+    pub fn lizard_spock() -> Game {
+        Game::new(vec![1, 2, 3, 4, 5], [0, 3, 6], "ABCDE", "VWXYZ", "XYZ").unwrap()
+    }
 
-        let a = hand_iter.next().unwrap();
-        let b = hand_iter.next().unwrap();
-        assert!(hand_iter.next().is_none());
-        res.push((a, b));
+    fn beats(&self, a: usize, b: usize) -> bool {
+        (a + self.shapes - b) % self.shapes % 2 == 1
     }
 
-    res
+    fn outcome(&self, mine: usize, theirs: usize) -> Outcome {
+        match (self.beats(mine, theirs), self.beats(theirs, mine)) {
+            (true, _) => Outcome::Win,
+            (_, true) => Outcome::Loss,
+            _ => Outcome::Draw,
+        }
+    }
+
+    /// The shape to play against `theirs` for `outcome`, the first in order if several would do.
+    fn respond(&self, theirs: usize, outcome: Outcome) -> usize {
+        (0..self.shapes)
+            .find(|&mine| self.outcome(mine, theirs) == outcome)
+            .unwrap()
+    }
+
+    fn score(&self, mine: usize, theirs: usize) -> i64 {
+        self.shape_scores[mine] + self.outcome_scores[self.outcome(mine, theirs) as usize]
+    }
+
+    /// Score a round of the strategy guide, a symbol for the opponent's shape and one for the
+    /// response or outcome.
+    fn score_round(&self, line: &str, column: Column) -> Result<i64, String> {
+        let symbols = line
+            .split_whitespace()
+            .map(|s| {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(c),
+                    _ => Err(format!("Invalid symbol '{}'", s)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let [a, b] = symbols[..] else {
+            return Err(format!("Expected two symbols in '{}'", line));
+        };
+
+        let theirs = lookup(&self.opponent, a)?;
+        let mine = match column {
+            Column::Shape => lookup(&self.response, b)?,
+            Column::Outcome => self.respond(theirs, Outcome::ALL[lookup(&self.outcomes, b)?]),
+        };
+        Ok(self.score(mine, theirs))
+    }
+
+    fn total_score(&self, input: &str, column: Column) -> Result<i64, String> {
+        input
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                self.score_round(line, column)
+                    .map_err(|e| format!("Line {}: {}", i + 1, e))
+            })
+            .sum()
+    }
+}
+
+fn lookup(symbols: &[char], c: char) -> Result<usize, String> {
+    symbols
+        .iter()
+        .position(|&s| s == c)
+        .ok_or(format!("Unknown symbol '{}'", c))
 }
 
 pub fn part1() -> i64 {
-    load()
-        .into_iter()
-        .map(|(opponent_hand, my_hand)| score_part1(opponent_hand, my_hand))
-        .sum::<i64>()
+    Game::classic()
+        .total_score(include_str!("day2.txt"), Column::Shape)
+        .unwrap()
 }
 
 pub fn part2() -> i64 {
-    load()
-        .into_iter()
-        .map(|(opponent_hand, outcome)| score_part2(opponent_hand, outcome))
-        .sum::<i64>()
+    Game::classic()
+        .total_score(include_str!("day2.txt"), Column::Outcome)
+        .unwrap()
 }
 
+/// Score a strategy guide both ways with a preset game, optionally changing its tables.
+pub fn report(args: &[String]) -> Result<(), String> {
+    let usage = || {
+        "Usage: rps [--game=classic|lizard-spock] [--scores=N,...] [--outcomes=L,D,W] \
+         [--opponent=SYMBOLS] [--response=SYMBOLS] [--guide=SYMBOLS] [FILE]"
+            .to_string()
+    };
+    let numbers = |s: &str| {
+        s.split(',')
+            .map(|n| {
+                n.parse::<i64>()
+                    .map_err(|_| format!("Invalid score '{}'", n))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    let mut game = Game::classic();
+    let mut scores = None;
+    let mut outcomes = None;
+    let mut opponent = None;
+    let mut response = None;
+    let mut guide = None;
+    let mut file = None;
+    for arg in args {
+        match arg.split_once('=') {
+            Some(("--game", "classic")) => game = Game::classic(),
+            Some(("--game", "lizard-spock")) => game = Game::lizard_spock(),
+            Some(("--scores", s)) => scores = Some(numbers(s)?),
+            Some(("--outcomes", s)) => match numbers(s)?[..] {
+                [loss, draw, win] => outcomes = Some([loss, draw, win]),
+                _ => return Err(format!("Expected three outcome scores, got '{}'", s)),
+            },
+            Some(("--opponent", s)) => opponent = Some(s),
+            Some(("--response", s)) => response = Some(s),
+            Some(("--guide", s)) => guide = Some(s),
+            _ if file.is_none() && !arg.starts_with("--") => file = Some(arg),
+            _ => return Err(usage()),
+        }
+    }
+
+    let symbols = |s: Option<&str>, preset: &[char]| match s {
+        Some(s) => s.to_string(),
+        None => preset.iter().collect(),
+    };
+    let game = Game::new(
+        scores.unwrap_or(game.shape_scores.clone()),
+        outcomes.unwrap_or(game.outcome_scores),
+        &symbols(opponent, &game.opponent),
+        &symbols(response, &game.response),
+        &symbols(guide, &game.outcomes),
+    )?;
+
+    let input = match file {
+        Some(file) => std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?,
+        None => include_str!("day2.txt").to_string(),
+    };
+    println!(
+        "Second column as shapes: {}",
+        game.total_score(&input, Column::Shape)?
+    );
+    println!(
+        "Second column as outcomes: {}",
+        game.total_score(&input, Column::Outcome)?
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+static EXAMPLE: &str = "A Y
+B X
+C Z
+";
+
 #[test]
 fn test_part1() {
     assert_eq!(part1(), 10310);
@@ -78,3 +254,119 @@ fn test_part1() {
 fn test_part2() {
     assert_eq!(part2(), 14859);
 }
+
+#[test]
+fn test_example() {
+    let game = Game::classic();
+    let rounds = |column| {
+        EXAMPLE
+            .lines()
+            .map(|line| game.score_round(line, column).unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(rounds(Column::Shape), [8, 1, 6]);
+    assert_eq!(rounds(Column::Outcome), [4, 1, 7]);
+    assert_eq!(game.total_score(EXAMPLE, Column::Shape), Ok(15));
+    assert_eq!(game.total_score(EXAMPLE, Column::Outcome), Ok(12));
+}
+
+#[test]
+fn test_classic_tables() {
+    // The scores of the original lookup tables, indexed by opponent and then own shape.
+    let game = Game::classic();
+    let table = [[4, 8, 3], [1, 5, 9], [7, 2, 6]];
+    for (theirs, row) in table.iter().enumerate() {
+        for (mine, &score) in row.iter().enumerate() {
+            assert_eq!(game.score(mine, theirs), score);
+        }
+    }
+}
+
+#[test]
+fn test_lizard_spock() {
+    let [rock, paper, scissors, spock, lizard] = [0, 1, 2, 3, 4];
+    let game = Game::lizard_spock();
+    let wins = [
+        (scissors, paper),
+        (paper, rock),
+        (rock, lizard),
+        (lizard, spock),
+        (spock, scissors),
+        (scissors, lizard),
+        (lizard, paper),
+        (paper, spock),
+        (spock, rock),
+        (rock, scissors),
+    ];
+    for a in 0..5 {
+        for b in 0..5 {
+            assert_eq!(game.beats(a, b), wins.contains(&(a, b)), "{} {}", a, b);
+        }
+        assert_eq!(game.outcome(a, a), Outcome::Draw);
+    }
+
+    // Every shape beats two and loses to two others, the first in order is chosen.
+    assert_eq!(game.respond(rock, Outcome::Win), paper);
+    assert_eq!(game.respond(rock, Outcome::Loss), scissors);
+    assert_eq!(game.respond(lizard, Outcome::Draw), lizard);
+    assert_eq!(game.score_round("E W", Column::Shape), Ok(2));
+    assert_eq!(game.score_round("D Z", Column::Outcome), Ok(8));
+}
+
+#[test]
+fn test_custom_scoring_and_symbols() {
+    let game = Game::new(vec![0, 0, 0], [-1, 0, 1], "rps", "RPS", "XYZ").unwrap();
+    assert_eq!(game.total_score("r P\np P\ns P\n", Column::Shape), Ok(0));
+    assert_eq!(game.total_score("r Z\ns Z\n", Column::Outcome), Ok(2));
+    assert_eq!(
+        game.total_score("r P\nA X\n", Column::Shape),
+        Err("Line 2: Unknown symbol 'A'".to_string())
+    );
+    assert_eq!(
+        game.score_round("r", Column::Shape),
+        Err("Expected two symbols in 'r'".to_string())
+    );
+    assert_eq!(
+        game.score_round("rr P", Column::Shape),
+        Err("Invalid symbol 'rr'".to_string())
+    );
+}
+
+#[test]
+fn test_many_shapes() {
+    // Seven shapes, each beating the three an odd number of steps before it.
+    let game = Game::new(vec![1; 7], [0, 3, 6], "ABCDEFG", "TUVWXYZ", "XYZ").unwrap();
+    assert_eq!(
+        (0..7).filter(|&b| game.beats(6, b)).collect::<Vec<_>>(),
+        [1, 3, 5]
+    );
+    assert_eq!(game.respond(0, Outcome::Win), 1);
+    assert_eq!(game.score_round("G Z", Column::Outcome), Ok(7));
+}
+
+#[test]
+fn test_invalid_games() {
+    let game = |scores: Vec<i64>, opponent, response, outcomes| {
+        Game::new(scores, [0, 3, 6], opponent, response, outcomes).unwrap_err()
+    };
+    assert_eq!(
+        game(vec![1, 2, 3, 4], "ABCD", "WXYZ", "XYZ"),
+        "A game needs an odd number of at least 3 shapes, got 4"
+    );
+    assert_eq!(
+        game(vec![1], "A", "X", "XYZ"),
+        "A game needs an odd number of at least 3 shapes, got 1"
+    );
+    assert_eq!(
+        game(vec![1, 2, 3], "AB", "XYZ", "XYZ"),
+        "Expected 3 opponent symbols, got 'AB'"
+    );
+    assert_eq!(
+        game(vec![1, 2, 3], "ABC", "XYX", "XYZ"),
+        "Duplicate response symbol 'X'"
+    );
+    assert_eq!(
+        game(vec![1, 2, 3], "ABC", "XYZ", "X Z"),
+        "Invalid outcome symbol ' '"
+    );
+}
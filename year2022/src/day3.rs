@@ -1,71 +1,204 @@
-fn load() -> Vec<&'static str> {
-    include_str!("day3.txt")
-        .lines()
-        .inspect(|line| assert!(line.chars().all(|c| c.is_ascii_alphabetic())))
-        .collect::<Vec<_>>()
+//! # Day 3: Rucksack Reorganization
+
+use advent_of_code_core::bitset::BitSet;
+use advent_of_code_core::group::Group;
+use advent_of_code_core::hash::FxHashMap;
+
+/// The symbols items are written with, in order of priority starting at 1.
+struct Alphabet {
+    symbols: Vec<char>,
+    priorities: FxHashMap<char, usize>,
 }
 
-fn char_value(c: char) -> u32 {
-    match c {
-        'a'..='z' => c as u32 - 'a' as u32 + 1,
-        'A'..='Z' => c as u32 - 'A' as u32 + 27,
-        _ => panic!("Unexpected character '{}'", c),
+impl Alphabet {
+    /// An alphabet of `symbols`, each of which may only appear once.
+    fn new(symbols: &str) -> Result<Alphabet, String> {
+        let symbols = symbols.chars().collect::<Vec<_>>();
+        let mut priorities = FxHashMap::default();
+        for (i, &c) in symbols.iter().enumerate() {
+            if priorities.insert(c, i + 1).is_some() {
+                return Err(format!("Duplicate symbol '{}' in alphabet", c));
+            }
+        }
+        Ok(Alphabet {
+            symbols,
+            priorities,
+        })
+    }
+
+    /// `a` to `z` with priorities 1 to 26, then `A` to `Z` with 27 to 52.
+    fn standard() -> Alphabet {
+        Alphabet::new("abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ").unwrap()
+    }
+
+    fn priority(&self, c: char) -> Result<usize, String> {
+        self.priorities
+            .get(&c)
+            .copied()
+            .ok_or(format!("Unexpected character '{}'", c))
+    }
+
+    fn symbol(&self, priority: usize) -> char {
+        self.symbols[priority - 1]
+    }
+
+    /// The set of priorities of the items in `s`.
+    fn items(&self, s: &str) -> Result<BitSet, String> {
+        s.chars().map(|c| self.priority(c)).collect()
     }
-}
 
-fn char_bitset(s: &str) -> u64 {
-    let mut bitset = 0;
-    for c in s.chars() {
-        bitset |= 1 << char_value(c);
+    fn format(&self, items: &BitSet) -> String {
+        items.iter().map(|p| self.symbol(p)).collect()
     }
-    bitset
 }
 
-fn bitset_sum(bitset: u64) -> i64 {
-    let mut sum = 0;
-    for i in 0..53 {
-        if bitset & (1 << i) > 0 {
-            sum += i;
+/// How the items are packed: the number of equally sized compartments per rucksack, and the
+/// number of rucksacks per group of elves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Packing {
+    compartments: usize,
+    group_size: usize,
+}
+
+impl Packing {
+    const STANDARD: Packing = Packing {
+        compartments: 2,
+        group_size: 3,
+    };
+
+    /// The items in each compartment of a rucksack.
+    fn compartments(&self, alphabet: &Alphabet, rucksack: &str) -> Result<Group, String> {
+        let len = rucksack.chars().count();
+        if len == 0 || !len.is_multiple_of(self.compartments) {
+            return Err(format!(
+                "Rucksack '{}' does not split into {} compartments",
+                rucksack, self.compartments
+            ));
         }
+
+        let chars = rucksack.chars().collect::<Vec<_>>();
+        chars
+            .chunks(len / self.compartments)
+            .map(|chunk| alphabet.items(&chunk.iter().collect::<String>()))
+            .collect()
     }
-    sum
+
+    /// The items of every rucksack in each group of elves.
+    fn groups(&self, alphabet: &Alphabet, rucksacks: &[&str]) -> Result<Vec<Group>, String> {
+        if !rucksacks.len().is_multiple_of(self.group_size) {
+            return Err(format!(
+                "{} rucksacks do not split into groups of {}",
+                rucksacks.len(),
+                self.group_size
+            ));
+        }
+
+        rucksacks
+            .chunks(self.group_size)
+            .map(|group| group.iter().map(|r| alphabet.items(r)).collect())
+            .collect()
+    }
+}
+
+fn load() -> Vec<&'static str> {
+    include_str!("day3.txt").lines().collect::<Vec<_>>()
+}
+
+fn priority_sum(items: &BitSet) -> i64 {
+    items.iter().sum::<usize>() as i64
 }
 
 pub fn part1() -> i64 {
+    let alphabet = Alphabet::standard();
     load()
         .into_iter()
-        .map(|line: &str| -> i64 {
-            assert_eq!(line.len() % 2, 0);
-            assert!(line.len() >= 2);
-            let compartments = line.split_at(line.len() / 2);
-            bitset_sum(char_bitset(compartments.0) & char_bitset(compartments.1))
+        .map(|line| {
+            let compartments = Packing::STANDARD.compartments(&alphabet, line).unwrap();
+            priority_sum(&compartments.intersection())
         })
         .sum::<i64>()
 }
 
 pub fn part2() -> i64 {
-    let rucksacks = load();
-    assert_eq!(rucksacks.len() % 3, 0);
-
-    rucksacks
-        .chunks_exact(3)
-        .map(|chunk| {
-            let a = char_bitset(chunk[0]);
-            let b = char_bitset(chunk[1]);
-            let c = char_bitset(chunk[2]);
-            let common = a & b & c;
-            assert_eq!(1, common.count_ones());
-            bitset_sum(common)
+    let alphabet = Alphabet::standard();
+    Packing::STANDARD
+        .groups(&alphabet, &load())
+        .unwrap()
+        .iter()
+        .map(|group| {
+            let badge = group.intersection();
+            assert_eq!(badge.len(), 1);
+            priority_sum(&badge)
         })
         .sum::<i64>()
 }
 
+/// Print the items shared by the compartments of each rucksack and by the rucksacks of each
+/// group, for the puzzle input or `FILE`.
+pub fn report(args: &[String]) -> Result<(), String> {
+    let usage = || "Usage: rucksacks [--compartments=N] [--group=N] [FILE]".to_string();
+    let mut packing = Packing::STANDARD;
+    let mut file = None;
+    for arg in args {
+        let number = |s: &str| match s.parse::<usize>() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(format!("Invalid count '{}'", s)),
+        };
+        match arg.split_once('=') {
+            Some(("--compartments", n)) => packing.compartments = number(n)?,
+            Some(("--group", n)) => packing.group_size = number(n)?,
+            _ if file.is_none() && !arg.starts_with("--") => file = Some(arg),
+            _ => return Err(usage()),
+        }
+    }
+
+    let input = match file {
+        Some(file) => std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?,
+        None => include_str!("day3.txt").to_string(),
+    };
+    let rucksacks = input.lines().collect::<Vec<_>>();
+    let alphabet = Alphabet::standard();
+    let groups = packing.groups(&alphabet, &rucksacks)?;
+
+    let mut total = 0;
+    for (i, rucksack) in rucksacks.iter().enumerate() {
+        let shared = packing.compartments(&alphabet, rucksack)?.intersection();
+        total += priority_sum(&shared);
+        println!("Rucksack {:>4}: {}", i + 1, alphabet.format(&shared));
+    }
+    println!("Rucksack priorities: {}", total);
+
+    let mut total = 0;
+    for (i, group) in groups.iter().enumerate() {
+        let shared = group.intersection();
+        total += priority_sum(&shared);
+        println!("Group {:>4}: {}", i + 1, alphabet.format(&shared));
+    }
+    println!("Group priorities: {}", total);
+    Ok(())
+}
+
+#[cfg(test)]
+static EXAMPLE: [&str; 6] = [
+    "vJrwpWtwJgWrhcsFMMfFFhFp",
+    "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
+    "PmmdzqPrVvPwwTWBwg",
+    "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
+    "ttgJtRGJQctTZtZT",
+    "CrZsJsPPZsGzwwsLwLmpwMDw",
+];
+
 #[test]
 fn test_char_value() {
-    assert_eq!(1, char_value('a'));
-    assert_eq!(26, char_value('z'));
-    assert_eq!(27, char_value('A'));
-    assert_eq!(52, char_value('Z'));
+    let alphabet = Alphabet::standard();
+    assert_eq!(Ok(1), alphabet.priority('a'));
+    assert_eq!(Ok(26), alphabet.priority('z'));
+    assert_eq!(Ok(27), alphabet.priority('A'));
+    assert_eq!(Ok(52), alphabet.priority('Z'));
+    assert_eq!(
+        Err("Unexpected character '1'".to_string()),
+        alphabet.priority('1')
+    );
 }
 
 #[test]
@@ -77,3 +210,76 @@ fn test_part1() {
 fn test_part2() {
     assert_eq!(part2(), 2805);
 }
+
+#[test]
+fn test_example() {
+    let alphabet = Alphabet::standard();
+    let shared = EXAMPLE
+        .iter()
+        .map(|r| {
+            let items = Packing::STANDARD.compartments(&alphabet, r).unwrap();
+            alphabet.format(&items.intersection())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(shared, ["p", "L", "P", "v", "t", "s"]);
+
+    let groups = Packing::STANDARD.groups(&alphabet, &EXAMPLE).unwrap();
+    let badges = groups
+        .iter()
+        .map(|g| alphabet.format(&g.intersection()))
+        .collect::<Vec<_>>();
+    assert_eq!(badges, ["r", "Z"]);
+    assert_eq!(
+        groups
+            .iter()
+            .map(|g| priority_sum(&g.intersection()))
+            .sum::<i64>(),
+        70
+    );
+}
+
+#[test]
+fn test_configurable_packing() {
+    let alphabet = Alphabet::standard();
+    let packing = Packing {
+        compartments: 3,
+        group_size: 2,
+    };
+    let items = packing.compartments(&alphabet, "abXcdXefX").unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(alphabet.format(&items.intersection()), "X");
+    assert_eq!(
+        packing.compartments(&alphabet, "abcd").unwrap_err(),
+        "Rucksack 'abcd' does not split into 3 compartments"
+    );
+
+    let groups = packing.groups(&alphabet, &EXAMPLE).unwrap();
+    assert_eq!(groups.len(), 3);
+    assert_eq!(alphabet.format(&groups[0].intersection()), "frsFM");
+    assert_eq!(
+        packing.groups(&alphabet, &EXAMPLE[..5]).unwrap_err(),
+        "5 rucksacks do not split into groups of 2"
+    );
+}
+
+#[test]
+fn test_large_alphabet() {
+    // Printable ASCII, more symbols than fit in a u64.
+    let symbols = (b'!'..=b'~').map(char::from).collect::<String>();
+    let alphabet = Alphabet::new(&symbols).unwrap();
+    assert_eq!(alphabet.priority('~'), Ok(94));
+
+    let packing = Packing {
+        compartments: 2,
+        group_size: 1,
+    };
+    let items = packing.compartments(&alphabet, "~}!a|}").unwrap();
+    assert_eq!(alphabet.format(&items.intersection()), "}");
+    assert_eq!(priority_sum(&items.intersection()), 93);
+    assert!(packing.compartments(&alphabet, "a b ").is_err());
+
+    assert_eq!(
+        Alphabet::new("abcb").err(),
+        Some("Duplicate symbol 'b' in alphabet".to_string())
+    );
+}
@@ -2,7 +2,7 @@ mod day1;
 mod day2;
 mod day3;
 
-use advent_of_code_core::{menu, Year};
+use advent_of_code_core::{menu_with_commands, Command, Year};
 
 pub static SOLUTIONS: Year = [
     (Some(day1::part1), Some(day1::part2)),
@@ -31,6 +31,27 @@ pub static SOLUTIONS: Year = [
     (None, None),
 ];
 
+advent_of_code_core::sources!(SOURCES, SOLUTIONS, [1, 2, 3]);
+
+static COMMANDS: [Command; 3] = [
+    (
+        "calories",
+        "[K] [FILE]  list the K elves carrying the most day 1 calories",
+        day1::report,
+    ),
+    (
+        "rps",
+        "[--game=classic|lizard-spock] [--scores=N,...] [--outcomes=L,D,W] [--opponent=SYMBOLS] \
+         [--response=SYMBOLS] [--guide=SYMBOLS] [FILE]  score a day 2 strategy guide",
+        day2::report,
+    ),
+    (
+        "rucksacks",
+        "[--compartments=N] [--group=N] [FILE]  show the items shared in day 3 rucksacks",
+        day3::report,
+    ),
+];
+
 fn main() -> Result<(), String> {
-    menu(&SOLUTIONS)
+    menu_with_commands(&SOLUTIONS, &SOURCES, &COMMANDS)
 }
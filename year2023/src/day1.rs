@@ -1,147 +1,171 @@
-use std::ops::RangeInclusive;
-
-static NUMERIC_ASCII_RANGE: RangeInclusive<u8> = b'0'..=b'9';
-
-static NUMBER_NAMES: [(&'static [u8], u8); 9] = [
-    (b"one", 1),
-    (b"two", 2),
-    (b"three", 3),
-    (b"four", 4),
-    (b"five", 5),
-    (b"six", 6),
-    (b"seven", 7),
-    (b"eight", 8),
-    (b"nine", 9),
+//! # Day 1: Trebuchet?!
+
+use crate::matcher::{Match, Matcher};
+
+static DIGITS: [(&str, i64); 9] = [
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
 ];
 
+/// The number words of each language, for one to nine.
+static LANGUAGES: [(&str, [&str; 9]); 4] = [
+    (
+        "english",
+        [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ],
+    ),
+    (
+        "german",
+        [
+            "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+        ],
+    ),
+    (
+        "french",
+        [
+            "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+        ],
+    ),
+    (
+        "spanish",
+        [
+            "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+        ],
+    ),
+];
+
+/// A matcher for the digits and the number words of `languages`.
+fn number_matcher(languages: &[&str]) -> Result<Matcher<i64>, String> {
+    let mut words = DIGITS.to_vec();
+    for &language in languages {
+        let (_, names) = LANGUAGES
+            .iter()
+            .find(|(name, _)| *name == language)
+            .ok_or(format!("Unknown language '{}'", language))?;
+        words.extend(names.iter().copied().zip(1..));
+    }
+    Ok(Matcher::new(words))
+}
+
 fn load() -> Vec<&'static str> {
     let data = include_str!("day1.txt");
     data.lines().collect()
 }
 
-fn part1_find_first(s: &'static str) -> Option<i64> {
-    s.chars()
-        .find(|c| c.is_numeric())
-        .and_then(|i| i.to_digit(10))
-        .map(|i| i as i64)
+/// The values of the first and last match by start position, found in a single pass. Matches are
+/// reported by where they end, so overlapping words can start out of order.
+fn first_and_last(matches: impl IntoIterator<Item = Match<i64>>) -> Option<(i64, i64)> {
+    matches
+        .into_iter()
+        .fold(
+            None,
+            |ends: Option<(Match<i64>, Match<i64>)>, m| match ends {
+                None => Some((m, m)),
+                Some((first, last)) => Some((
+                    if m.start < first.start { m } else { first },
+                    if m.start >= last.start { m } else { last },
+                )),
+            },
+        )
+        .map(|(first, last)| (first.value, last.value))
 }
 
-fn part1_find_last(s: &'static str) -> Option<i64> {
-    s.chars()
-        .rev()
-        .find(|c| c.is_numeric())
-        .and_then(|i| i.to_digit(10))
-        .map(|i| i as i64)
+/// The first and last number of a line, as a two digit number.
+fn calibration_value(matches: impl IntoIterator<Item = Match<i64>>) -> Option<i64> {
+    let (first, last) = first_and_last(matches)?;
+    Some(first * 10 + last)
 }
 
-pub fn part1() -> i64 {
+fn calibration_sum(languages: &[&str]) -> i64 {
+    let matcher = number_matcher(languages).unwrap();
     load()
         .into_iter()
-        .map(|s| {
-            let first = part1_find_first(s).unwrap();
-            let last = part1_find_last(s).unwrap();
-            first * 10 + last
-        })
+        .map(|s| calibration_value(matcher.find_iter(s)).unwrap())
         .sum()
 }
 
-fn part2_find_first(s: &'static str) -> Option<i64> {
-    let data = s.as_bytes();
-
-    for i in 0..data.len() {
-        if NUMERIC_ASCII_RANGE.contains(&data[i]) {
-            return Some((data[i] - b'0') as i64);
-        }
-
-        for number in NUMBER_NAMES {
-            let end = i + number.0.len();
-            if end > data.len() {
-                continue;
-            }
-            let target = &data[i..end];
-            if target == number.0 {
-                return Some(number.1 as i64);
-            }
-        }
-    }
-    None
+pub fn part1() -> i64 {
+    calibration_sum(&[])
 }
 
-fn part2_find_last(s: &'static str) -> Option<i64> {
-    let data = s.as_bytes();
-
-    for i in (0..data.len()).rev() {
-        if NUMERIC_ASCII_RANGE.contains(&data[i]) {
-            return Some((data[i] - b'0') as i64);
-        }
+pub fn part2() -> i64 {
+    calibration_sum(&["english"])
+}
 
-        for number in NUMBER_NAMES {
-            let start = (i as isize + 1) - number.0.len() as isize;
-            if start < 0 {
-                continue;
-            }
-            let start = start as usize;
-            let target = &data[start..(i + 1)];
-            if target == number.0 {
-                return Some(number.1 as i64);
+/// Print the numbers found on each line of the puzzle input or `FILE` with their positions,
+/// reading the number words of every `--language=NAME` given.
+pub fn report(args: &[String]) -> Result<(), String> {
+    let mut languages = Vec::new();
+    let mut file = None;
+    for arg in args {
+        match arg.strip_prefix("--language=") {
+            Some(language) => languages.push(language),
+            None if file.is_none() && !arg.starts_with("--") => file = Some(arg),
+            None => {
+                return Err(format!(
+                    "Usage: calibrate [--language={}]... [FILE]",
+                    LANGUAGES.map(|(name, _)| name).join("|")
+                ))
             }
         }
     }
 
-    None
-}
-
-pub fn part2() -> i64 {
-    load()
-        .into_iter()
-        .map(|s: &str| {
-            let first = part2_find_first(s).unwrap();
-            let last = part2_find_last(s).unwrap();
-            first * 10 + last
-        })
-        .sum()
-}
-
-#[test]
-fn test_part1_find_first() {
-    assert_eq!(part1_find_first("test"), None);
-    assert_eq!(part1_find_first("1abc2"), Some(1));
-    assert_eq!(part1_find_first("pqr3stu8vwx"), Some(3));
-    assert_eq!(part1_find_first("a1b2c3d4e5f"), Some(1));
-    assert_eq!(part1_find_first("treb7uchet"), Some(7));
-}
-
-#[test]
-fn test_part1_find_last() {
-    assert_eq!(part1_find_last("test"), None);
-    assert_eq!(part1_find_last("1abc2"), Some(2));
-    assert_eq!(part1_find_last("pqr3stu8vwx"), Some(8));
-    assert_eq!(part1_find_last("a1b2c3d4e5f"), Some(5));
-    assert_eq!(part1_find_last("treb7uchet"), Some(7));
+    let input = match file {
+        Some(file) => std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?,
+        None => include_str!("day1.txt").to_string(),
+    };
+    let matcher = number_matcher(&languages)?;
+
+    let mut sum = 0;
+    for (i, line) in input.lines().enumerate() {
+        let matches = matcher.find_iter(line).collect::<Vec<_>>();
+        let value = calibration_value(matches.iter().copied()).ok_or(format!(
+            "Line {}: no numbers in '{}'",
+            i + 1,
+            line
+        ))?;
+        sum += value;
+        let found = matches
+            .iter()
+            .map(|m| format!("{}@{}", &line[m.start..m.end], m.start))
+            .collect::<Vec<_>>();
+        println!("{:>4}: {:>2}  {}", i + 1, value, found.join(" "));
+    }
+    println!("Sum: {}", sum);
+    Ok(())
 }
 
 #[test]
-fn test_part2_find_first() {
-    assert_eq!(part2_find_first("test"), None);
-    assert_eq!(part2_find_first("two1nine"), Some(2));
-    assert_eq!(part2_find_first("eightwothree"), Some(8));
-    assert_eq!(part2_find_first("abcone2threexyz"), Some(1));
-    assert_eq!(part2_find_first("xtwone3four"), Some(2));
-    assert_eq!(part2_find_first("4nineeightseven2"), Some(4));
-    assert_eq!(part2_find_first("zoneight234"), Some(1));
-    assert_eq!(part2_find_first("7pqrstsixteen"), Some(7));
+fn test_part1_first_and_last() {
+    let digits = number_matcher(&[]).unwrap();
+    let ends = |s| first_and_last(digits.find_iter(s));
+    assert_eq!(ends("test"), None);
+    assert_eq!(ends("1abc2"), Some((1, 2)));
+    assert_eq!(ends("pqr3stu8vwx"), Some((3, 8)));
+    assert_eq!(ends("a1b2c3d4e5f"), Some((1, 5)));
+    assert_eq!(ends("treb7uchet"), Some((7, 7)));
 }
 
 #[test]
-fn test_part2_find_last() {
-    assert_eq!(part2_find_last("test"), None);
-    assert_eq!(part2_find_last("two1nine"), Some(9));
-    assert_eq!(part2_find_last("eightwothree"), Some(3));
-    assert_eq!(part2_find_last("abcone2threexyz"), Some(3));
-    assert_eq!(part2_find_last("xtwone3four"), Some(4));
-    assert_eq!(part2_find_last("4nineeightseven2"), Some(2));
-    assert_eq!(part2_find_last("zoneight234"), Some(4));
-    assert_eq!(part2_find_last("7pqrstsixteen"), Some(6));
+fn test_part2_first_and_last() {
+    let english = number_matcher(&["english"]).unwrap();
+    let ends = |s| first_and_last(english.find_iter(s));
+    assert_eq!(ends("test"), None);
+    assert_eq!(ends("two1nine"), Some((2, 9)));
+    assert_eq!(ends("eightwothree"), Some((8, 3)));
+    assert_eq!(ends("abcone2threexyz"), Some((1, 3)));
+    assert_eq!(ends("xtwone3four"), Some((2, 4)));
+    assert_eq!(ends("4nineeightseven2"), Some((4, 2)));
+    assert_eq!(ends("zoneight234"), Some((1, 4)));
+    assert_eq!(ends("7pqrstsixteen"), Some((7, 6)));
 }
 
 #[test]
@@ -153,3 +177,30 @@ fn test_part1() {
 fn test_part2() {
     assert_eq!(part2(), 53592);
 }
+
+#[test]
+fn test_other_languages() {
+    let german = number_matcher(&["german"]).unwrap();
+    assert_eq!(
+        calibration_value(german.find_iter("xfünfzweiacht")),
+        Some(58)
+    );
+    assert_eq!(
+        calibration_value(german.find_iter("seinsiebeneun")),
+        Some(19)
+    );
+
+    let french = number_matcher(&["french"]).unwrap();
+    assert_eq!(calibration_value(french.find_iter("deuxneufun")), Some(21));
+    assert_eq!(calibration_value(french.find_iter("7quatre")), Some(74));
+
+    // Words of several languages can overlap each other.
+    let mixed = number_matcher(&["english", "spanish"]).unwrap();
+    assert_eq!(calibration_value(mixed.find_iter("dosevenueve")), Some(29));
+    assert_eq!(calibration_value(mixed.find_iter("treseis")), Some(36));
+
+    assert_eq!(
+        number_matcher(&["klingon"]).err(),
+        Some("Unknown language 'klingon'".to_string())
+    );
+}
@@ -1,70 +1,105 @@
-#[derive(Debug, PartialEq, Eq)]
-struct Round {
-    red: u32,
-    green: u32,
-    blue: u32,
-}
+//! # Day 2: Cube Conundrum
+
+use std::collections::BTreeMap;
+
+/// The number of cubes of each colour, in a round or in the bag.
+type Cubes<'a> = BTreeMap<&'a str, u32>;
 
 #[derive(Debug, PartialEq, Eq)]
-struct Game {
+struct Game<'a> {
     id: u32,
-    rounds: Vec<Round>,
+    rounds: Vec<Cubes<'a>>,
 }
 
-impl Game {
-    fn parse(s: &str) -> Game {
-        static PREFIX: &'static str = "Game ";
+/// The bag of the puzzle.
+static BAG: &str = "12 red, 13 green, 14 blue";
 
-        let mut main_parts = s.split(':');
-        let part_1: &str = main_parts.next().expect("cound not parse main_part_1");
-        let part_2 = main_parts.next().expect("cound not parse main_part_2");
+/// Parse a comma separated list of cube counts, `3 blue, 4 red`. Counts of the same colour
+/// are added up.
+fn parse_cubes(s: &str) -> Result<Cubes<'_>, String> {
+    let mut cubes = Cubes::new();
+    for part in s.split(',') {
+        let (count, colour) = part.trim().split_once(' ').ok_or(format!(
+            "Expected a count and a colour in '{}'",
+            part.trim()
+        ))?;
+        let count = count
+            .parse::<u32>()
+            .map_err(|e| format!("Invalid count '{}': {}", count, e))?;
+        let colour = colour.trim();
+        if colour.is_empty() || colour.contains(char::is_whitespace) {
+            return Err(format!("Invalid colour '{}'", colour));
+        }
+        *cubes.entry(colour).or_default() += count;
+    }
+    Ok(cubes)
+}
 
-        let part_1 = part_1.trim_start_matches(PREFIX);
-        let id = part_1.parse::<u32>().expect("cound not parse id");
+impl<'a> Game<'a> {
+    fn parse(s: &'a str) -> Result<Game<'a>, String> {
+        let (id, rounds) = s.split_once(':').ok_or(format!("Missing ':' in '{}'", s))?;
+        let id = id
+            .strip_prefix("Game ")
+            .ok_or(format!("Expected 'Game ID' in '{}'", id))?;
+        let id = id
+            .parse::<u32>()
+            .map_err(|e| format!("Invalid game id '{}': {}", id, e))?;
 
-        let mut rounds = vec![];
+        let rounds = rounds
+            .split(';')
+            .map(parse_cubes)
+            .collect::<Result<Vec<_>, _>>()?;
 
-        for round in part_2.split(';') {
-            let mut red: u32 = 0u32;
-            let mut green: u32 = 0u32;
-            let mut blue: u32 = 0u32;
+        Ok(Game { id, rounds })
+    }
 
-            for color_part in round.split(',') {
-                let mut parts = color_part.split_whitespace();
-                let count: &str = parts.next().expect("cound not parse count");
-                let count = count.parse::<u32>().expect("cound not parse count to u32");
+    /// Whether every round could have been drawn from `bag`.
+    fn is_possible(&self, bag: &Cubes) -> bool {
+        self.rounds.iter().all(|round| {
+            round
+                .iter()
+                .all(|(colour, &count)| count <= bag.get(colour).copied().unwrap_or(0))
+        })
+    }
 
-                let color: &str = parts.next().expect("cound not parse color");
-                match color {
-                    "red" => red += count,
-                    "green" => green += count,
-                    "blue" => blue += count,
-                    s => panic!("unexpected color {}", s),
-                }
+    /// The fewest cubes of each colour seen that make the game possible.
+    fn minimum_bag(&self) -> Cubes<'a> {
+        let mut bag = Cubes::new();
+        for round in &self.rounds {
+            for (&colour, &count) in round {
+                let max = bag.entry(colour).or_default();
+                *max = u32::max(*max, count);
             }
-
-            rounds.push(Round { red, green, blue });
         }
-
-        Game { id, rounds }
+        bag
     }
 }
 
-fn load() -> Vec<Game> {
-    include_str!("day2.txt")
+/// The product of the number of cubes of each of `colours` in `bag`, zero if one is missing.
+fn power(bag: &Cubes, colours: &[&str]) -> u64 {
+    colours
+        .iter()
+        .map(|colour| bag.get(colour).copied().unwrap_or(0) as u64)
+        .product()
+}
+
+fn parse(input: &str) -> Result<Vec<Game<'_>>, String> {
+    input
         .lines()
-        .map(|l| Game::parse(l))
-        .collect::<Vec<_>>()
+        .enumerate()
+        .map(|(i, line)| Game::parse(line).map_err(|e| format!("Line {}: {}", i + 1, e)))
+        .collect()
+}
+
+fn load() -> Vec<Game<'static>> {
+    parse(include_str!("day2.txt")).unwrap()
 }
 
 pub fn part1() -> i64 {
+    let bag = parse_cubes(BAG).unwrap();
     load()
         .into_iter()
-        .filter(|g| {
-            g.rounds
-                .iter()
-                .all(|r| r.red <= 12 && r.green <= 13 && r.blue <= 14)
-        })
+        .filter(|g| g.is_possible(&bag))
         .map(|g| g.id)
         .sum::<u32>() as i64
 }
@@ -72,42 +107,145 @@ pub fn part1() -> i64 {
 pub fn part2() -> i64 {
     load()
         .into_iter()
-        .map(|g| {
-            let red = g.rounds.iter().map(|r| r.red).max().unwrap();
-            let green = g.rounds.iter().map(|r| r.green).max().unwrap();
-            let blue = g.rounds.iter().map(|r| r.blue).max().unwrap();
-            (red * green * blue) as i64
-        })
+        .map(|g| power(&g.minimum_bag(), &["red", "green", "blue"]) as i64)
         .sum::<i64>()
 }
 
+/// Print which games of the puzzle input or `FILE` are possible with the bag given by
+/// `--bag="12 red, 13 green, 14 blue"`, and the minimum bag of each game.
+pub fn report(args: &[String]) -> Result<(), String> {
+    let mut bag = BAG;
+    let mut file = None;
+    for arg in args {
+        match arg.strip_prefix("--bag=") {
+            Some(b) => bag = b,
+            None if file.is_none() && !arg.starts_with("--") => file = Some(arg),
+            None => return Err("Usage: cubes [--bag=\"N COLOUR, ...\"] [FILE]".to_string()),
+        }
+    }
+
+    let bag = parse_cubes(bag).map_err(|e| format!("Invalid bag: {}", e))?;
+    let input = match file {
+        Some(file) => std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?,
+        None => include_str!("day2.txt").to_string(),
+    };
+    let games = parse(&input)?;
+
+    let format = |cubes: &Cubes| {
+        cubes
+            .iter()
+            .map(|(colour, count)| format!("{} {}", count, colour))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut possible = 0;
+    for game in &games {
+        let is_possible = game.is_possible(&bag);
+        possible += is_possible as u32 * game.id;
+        println!(
+            "Game {:>3}: {:<10} minimum bag {}",
+            game.id,
+            if is_possible {
+                "possible"
+            } else {
+                "impossible"
+            },
+            format(&game.minimum_bag())
+        );
+    }
+    println!(
+        "Sum of possible game ids with {}: {}",
+        format(&bag),
+        possible
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+static EXAMPLE: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+";
+
 #[test]
 fn test_parse() {
     assert_eq!(
         Game::parse("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"),
-        Game {
+        Ok(Game {
             id: 1,
             rounds: vec![
-                Round {
-                    red: 4,
-                    green: 0,
-                    blue: 3
-                },
-                Round {
-                    red: 1,
-                    green: 2,
-                    blue: 6
-                },
-                Round {
-                    red: 0,
-                    green: 2,
-                    blue: 0
-                },
+                Cubes::from([("red", 4), ("blue", 3)]),
+                Cubes::from([("red", 1), ("green", 2), ("blue", 6)]),
+                Cubes::from([("green", 2)]),
             ]
-        }
+        })
+    );
+    assert_eq!(
+        parse_cubes("1 red, 2 purple, 3 red"),
+        Ok(Cubes::from([("red", 4), ("purple", 2)]))
     );
 }
 
+#[test]
+fn test_parse_errors() {
+    let error = |s| Game::parse(s).unwrap_err();
+    assert_eq!(error("Game 1 3 blue"), "Missing ':' in 'Game 1 3 blue'");
+    assert_eq!(error("Match 1: 3 blue"), "Expected 'Game ID' in 'Match 1'");
+    assert!(error("Game x: 3 blue").starts_with("Invalid game id 'x': "));
+    assert_eq!(
+        error("Game 1: 3 blue;"),
+        "Expected a count and a colour in ''"
+    );
+    assert!(error("Game 1: many blue").starts_with("Invalid count 'many': "));
+    assert_eq!(error("Game 1: 3 dark blue"), "Invalid colour 'dark blue'");
+    assert_eq!(
+        parse("Game 1: 1 red\nGame 2: red").unwrap_err(),
+        "Line 2: Expected a count and a colour in 'red'"
+    );
+}
+
+#[test]
+fn test_example() {
+    let games = parse(EXAMPLE).unwrap();
+    let bag = parse_cubes(BAG).unwrap();
+    let possible = games
+        .iter()
+        .filter(|g| g.is_possible(&bag))
+        .map(|g| g.id)
+        .collect::<Vec<_>>();
+    assert_eq!(possible, [1, 2, 5]);
+
+    let powers = games
+        .iter()
+        .map(|g| power(&g.minimum_bag(), &["red", "green", "blue"]))
+        .collect::<Vec<_>>();
+    assert_eq!(powers, [48, 12, 1560, 630, 36]);
+}
+
+#[test]
+fn test_other_colours() {
+    let games = parse(
+        "Game 1: 2 cyan, 1 magenta; 4 cyan
+Game 2: 1 yellow; 3 magenta
+",
+    )
+    .unwrap();
+    assert_eq!(
+        games[0].minimum_bag(),
+        Cubes::from([("cyan", 4), ("magenta", 1)])
+    );
+    assert_eq!(power(&games[0].minimum_bag(), &["cyan", "magenta"]), 4);
+    assert_eq!(power(&games[1].minimum_bag(), &["cyan", "magenta"]), 0);
+
+    // Colours missing from the bag can not be drawn at all.
+    let bag = parse_cubes("4 cyan, 3 magenta").unwrap();
+    assert!(games[0].is_possible(&bag));
+    assert!(!games[1].is_possible(&bag));
+    assert!(games[1].is_possible(&games[1].minimum_bag()));
+}
+
 #[test]
 fn test_part1() {
     assert_eq!(part1(), 2101);
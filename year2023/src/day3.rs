@@ -1,32 +1,44 @@
-use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
-use std::ops::RangeInclusive;
+//! # Day 3: Gear Ratios
 
-static NUMERIC_ASCII_RANGE: RangeInclusive<u8> = b'0'..=b'9';
+use advent_of_code_core::hash::FxHashMap;
 
-fn load() -> Vec<&'static [u8]> {
-    include_str!("day3.txt")
-        .lines()
-        .map(|l| l.as_bytes())
-        .collect()
-}
-
-#[derive(Debug)]
+/// A number on row `y`, covering the columns `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Number {
     value: i64,
+    y: usize,
     start: usize,
     end: usize,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Symbol {
+    symbol: u8,
+    x: usize,
+    y: usize,
+}
+
 #[derive(Debug)]
 struct Parser<'a> {
     data: &'a [u8],
+    y: usize,
     pos: usize,
 }
 
+/// The numbers and symbols of an engine schematic, indexed by position. Every symbol knows the
+/// numbers next to it, diagonals included.
+struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    /// The indices of the numbers next to each symbol.
+    adjacent: Vec<Vec<usize>>,
+    /// The number of symbols next to each number.
+    touching: Vec<usize>,
+}
+
 impl<'a> Parser<'a> {
-    fn new(data: &'a [u8]) -> Parser<'a> {
-        Parser { data, pos: 0 }
+    fn new(data: &'a [u8], y: usize) -> Parser<'a> {
+        Parser { data, y, pos: 0 }
     }
 }
 
@@ -34,7 +46,7 @@ impl<'a> std::iter::Iterator for Parser<'a> {
     type Item = Number;
 
     fn next(&mut self) -> Option<Number> {
-        while self.pos < self.data.len() && !NUMERIC_ASCII_RANGE.contains(&self.data[self.pos]) {
+        while self.pos < self.data.len() && !self.data[self.pos].is_ascii_digit() {
             self.pos += 1;
         }
 
@@ -43,7 +55,7 @@ impl<'a> std::iter::Iterator for Parser<'a> {
         }
 
         let start = self.pos;
-        while self.pos < self.data.len() && NUMERIC_ASCII_RANGE.contains(&self.data[self.pos]) {
+        while self.pos < self.data.len() && self.data[self.pos].is_ascii_digit() {
             self.pos += 1;
         }
         let end = self.pos;
@@ -54,22 +66,12 @@ impl<'a> std::iter::Iterator for Parser<'a> {
         let data_str = std::str::from_utf8(data).unwrap();
         let value = data_str.parse::<i64>().unwrap();
 
-        Some(Number { value, start, end })
-    }
-}
-
-#[derive(Debug, Eq)]
-struct SymbolPointer<'a>(&'a u8);
-
-impl<'a> PartialEq<SymbolPointer<'a>> for SymbolPointer<'a> {
-    fn eq(&self, other: &SymbolPointer<'a>) -> bool {
-        std::ptr::eq(self.0, other.0)
-    }
-}
-
-impl<'a> Hash for SymbolPointer<'a> {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        state.write_usize(self.0 as *const u8 as usize)
+        Some(Number {
+            value,
+            y: self.y,
+            start,
+            end,
+        })
     }
 }
 
@@ -78,138 +80,197 @@ fn is_symbol(b: u8) -> bool {
         return false;
     }
 
-    if NUMERIC_ASCII_RANGE.contains(&b) {
+    if b.is_ascii_digit() {
         return false;
     }
 
     true
 }
 
-fn find_neighboring_symbols<'a>(
-    num: &Number,
-    center: &'a [u8],
-    top: Option<&'a [u8]>,
-    bottom: Option<&'a [u8]>,
-) -> Vec<&'a u8> {
-    if let Some(top) = top {
-        assert_eq!(center.len(), top.len());
-    }
+impl Schematic {
+    fn parse(input: &str) -> Result<Schematic, String> {
+        let lines = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        if let Some(y) = lines.iter().position(|l| l.len() != lines[0].len()) {
+            return Err(format!(
+                "Line {} is {} wide, expected {}",
+                y + 1,
+                lines[y].len(),
+                lines[0].len()
+            ));
+        }
 
-    if let Some(bottom) = bottom {
-        assert_eq!(center.len(), bottom.len());
-    }
+        let numbers = lines
+            .iter()
+            .enumerate()
+            .flat_map(|(y, line)| Parser::new(line, y))
+            .collect::<Vec<_>>();
+
+        let mut symbols = Vec::new();
+        let mut symbol_at = FxHashMap::default();
+        for (y, line) in lines.iter().enumerate() {
+            for (x, &symbol) in line.iter().enumerate() {
+                if is_symbol(symbol) {
+                    symbol_at.insert((x, y), symbols.len());
+                    symbols.push(Symbol { symbol, x, y });
+                }
+            }
+        }
 
-    let mut symbols = vec![];
+        let mut adjacent = vec![Vec::new(); symbols.len()];
+        let mut touching = vec![0; numbers.len()];
+        for (i, n) in numbers.iter().enumerate() {
+            for y in n.y.saturating_sub(1)..=n.y + 1 {
+                for x in n.start.saturating_sub(1)..=n.end {
+                    if let Some(&s) = symbol_at.get(&(x, y)) {
+                        adjacent[s].push(i);
+                        touching[i] += 1;
+                    }
+                }
+            }
+        }
 
-    // check if there is a symbol to the right
-    if num.start >= 1 && is_symbol(center[num.start - 1]) {
-        symbols.push(&center[num.start - 1]);
+        Ok(Schematic {
+            numbers,
+            symbols,
+            adjacent,
+            touching,
+        })
     }
 
-    // check if there is a symbol to the left
-    if num.end + 1 <= center.len() && is_symbol(center[num.end]) {
-        symbols.push(&center[num.end]);
+    /// The numbers next to at least one symbol.
+    fn part_numbers(&self) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .zip(&self.touching)
+            .filter(|(_, &touching)| touching > 0)
+            .map(|(n, _)| n)
     }
 
-    // check if there is a symbol to the over
-    if let Some(top) = top {
-        let start = if num.start == 0 { 0 } else { num.start - 1 };
-        let end = if num.end == top.len() {
-            num.end
-        } else {
-            num.end + 1
-        };
-        for b in &top[start..end] {
-            if is_symbol(*b) {
-                symbols.push(b);
-            }
-        }
+    /// The numbers touching no symbol.
+    fn loose_numbers(&self) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .zip(&self.touching)
+            .filter(|(_, &touching)| touching == 0)
+            .map(|(n, _)| n)
     }
 
-    // check if there is a symbol to the under
-    if let Some(bottom) = bottom {
-        let start = if num.start == 0 { 0 } else { num.start - 1 };
-        let end = if num.end == bottom.len() {
-            num.end
-        } else {
-            num.end + 1
-        };
-        for b in &bottom[start..end] {
-            if is_symbol(*b) {
-                symbols.push(b);
-            }
-        }
+    /// Each `symbol` on the schematic with the numbers next to it.
+    fn parts_adjacent(&self, symbol: u8) -> impl Iterator<Item = (&Symbol, Vec<&Number>)> {
+        self.symbols
+            .iter()
+            .zip(&self.adjacent)
+            .filter(move |(s, _)| s.symbol == symbol)
+            .map(|(s, adjacent)| (s, adjacent.iter().map(|&i| &self.numbers[i]).collect()))
     }
 
-    symbols
+    /// Each `symbol` next to exactly `parts` numbers.
+    fn gears(&self, symbol: u8, parts: usize) -> impl Iterator<Item = (&Symbol, Vec<&Number>)> {
+        self.parts_adjacent(symbol)
+            .filter(move |(_, numbers)| numbers.len() == parts)
+    }
 }
 
-pub fn part1() -> i64 {
-    let lines = load();
-
-    let mut sum = 0;
-
-    for i in 0..lines.len() {
-        let center = lines[i];
-        let over = if i == 0 { None } else { Some(lines[i - 1]) };
-        let under = if i == lines.len() - 1 {
-            None
-        } else {
-            Some(lines[i + 1])
-        };
-        let numbers = Parser::new(center).collect::<Vec<_>>();
-        for n in numbers {
-            if !find_neighboring_symbols(&n, center, over, under).is_empty() {
-                sum += n.value
-            }
-        }
-    }
+fn load() -> Schematic {
+    Schematic::parse(include_str!("day3.txt")).unwrap()
+}
 
-    sum
+pub fn part1() -> i64 {
+    load().part_numbers().map(|n| n.value).sum()
 }
 
 pub fn part2() -> i64 {
-    let lines = load();
-
-    let mut symbol_groups: HashMap<SymbolPointer, Vec<i64>> = HashMap::new();
-
-    for i in 0..lines.len() {
-        let center = lines[i];
-        let over = if i == 0 { None } else { Some(lines[i - 1]) };
-        let under = if i == lines.len() - 1 {
-            None
-        } else {
-            Some(lines[i + 1])
-        };
-        let numbers = Parser::new(center).collect::<Vec<_>>();
-
-        for n in numbers {
-            let neighboring = find_neighboring_symbols(&n, center, over, under);
-            for neighbor in neighboring {
-                if neighbor != &b'*' {
-                    continue;
-                }
+    load()
+        .gears(b'*', 2)
+        .map(|(_, numbers)| numbers.iter().map(|n| n.value).product::<i64>())
+        .sum()
+}
 
-                let key = SymbolPointer(neighbor);
-                let group = symbol_groups.entry(key).or_insert_with(|| vec![]);
-                group.push(n.value);
-            }
-        }
+/// Print the symbols of the puzzle input or `FILE` with the number of parts next to them, the
+/// gears, and the numbers touching no symbol.
+pub fn report(args: &[String]) -> Result<(), String> {
+    let schematic = match args {
+        [] => load(),
+        [file] => Schematic::parse(
+            &std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?,
+        )?,
+        _ => return Err("Usage: schematic [FILE]".to_string()),
+    };
+
+    let mut kinds = schematic
+        .symbols
+        .iter()
+        .map(|s| s.symbol)
+        .collect::<Vec<_>>();
+    kinds.sort_unstable();
+    kinds.dedup();
+    for kind in kinds {
+        let (count, parts) = schematic
+            .parts_adjacent(kind)
+            .fold((0, 0), |(count, parts), (_, numbers)| {
+                (count + 1, parts + numbers.len())
+            });
+        println!(
+            "'{}': {:>4} symbols, {:>4} parts",
+            kind as char, count, parts
+        );
     }
 
-    symbol_groups
-        .into_iter()
-        .filter(|(_, values)| values.len() >= 2)
-        .map(|(_, values)| {
-            assert_eq!(values.len(), 2);
-            values.into_iter().product::<i64>()
-        })
-        .sum()
+    for (gear, numbers) in schematic.gears(b'*', 2) {
+        println!(
+            "Gear at {},{}: {} * {}",
+            gear.x + 1,
+            gear.y + 1,
+            numbers[0].value,
+            numbers[1].value
+        );
+    }
+
+    let loose = schematic
+        .loose_numbers()
+        .map(|n| format!("{}@{},{}", n.value, n.start + 1, n.y + 1))
+        .collect::<Vec<_>>();
+    println!("Numbers touching no symbol: {}", loose.join(" "));
+    Ok(())
+}
+
+#[cfg(test)]
+static EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+/// The symbols next to the first number on the middle row, or the only row.
+#[cfg(test)]
+fn neighboring_symbols(rows: &[&str], number: usize) -> Vec<u8> {
+    let schematic = Schematic::parse(&rows.join("\n")).unwrap();
+    let y = rows.len() / 2;
+    let n = schematic
+        .numbers
+        .iter()
+        .filter(|n| n.y == y)
+        .nth(number)
+        .unwrap();
+    let i = schematic.numbers.iter().position(|m| m == n).unwrap();
+    schematic
+        .symbols
+        .iter()
+        .zip(&schematic.adjacent)
+        .filter(|(_, adjacent)| adjacent.contains(&i))
+        .map(|(s, _)| s.symbol)
+        .collect()
 }
 
 #[test]
 fn test_parser() {
-    let result = Parser::new("467..114..".as_bytes()).collect::<Vec<_>>();
+    let result = Parser::new("467..114..".as_bytes(), 0).collect::<Vec<_>>();
     assert_eq!(result.len(), 2);
 
     assert_eq!(result[0].start, 0);
@@ -223,71 +284,85 @@ fn test_parser() {
 
 #[test]
 fn test_has_no_symbol() {
-    let over = None;
-    let center = "..592.....".as_bytes();
-    let under = None;
-    let numbers = Parser::new(center).collect::<Vec<_>>();
-    assert_eq!(numbers.len(), 1);
-
-    let symbols = find_neighboring_symbols(&numbers[0], center, over, under);
-    assert_eq!(symbols.len(), 0);
+    assert_eq!(neighboring_symbols(&["..592....."], 0), []);
 }
 
 #[test]
 fn test_has_symbol_right() {
-    let over = None;
-    let center = "617*......".as_bytes();
-    let under = None;
-    let numbers = Parser::new(center).collect::<Vec<_>>();
-    assert_eq!(numbers.len(), 1);
-
-    let symbols = find_neighboring_symbols(&numbers[0], center, over, under);
-    assert_eq!(symbols.len(), 1);
-    assert_eq!(symbols[0], &b'*');
+    assert_eq!(neighboring_symbols(&["617*......"], 0), [b'*']);
 }
 
 #[test]
 fn test_has_symbol_left() {
-    let over = None;
-    let center = "....*617......".as_bytes();
-    let under = None;
-    let numbers = Parser::new(center).collect::<Vec<_>>();
-
-    let symbols = find_neighboring_symbols(&numbers[0], center, over, under);
-    assert_eq!(symbols.len(), 1);
-    assert_eq!(symbols[0], &b'*');
+    assert_eq!(neighboring_symbols(&["....*617......"], 0), [b'*']);
 }
 
 #[test]
 fn test_has_symbol_over() {
-    let over = Some(".....*....".as_bytes());
-    let center = ".664.598..".as_bytes();
-    let under = None;
-    let numbers = Parser::new(center).collect::<Vec<_>>();
-    assert_eq!(numbers.len(), 2);
-
-    let symbols_0 = find_neighboring_symbols(&numbers[0], center, over, under);
-    assert_eq!(symbols_0.len(), 0);
-
-    let symbols_1 = find_neighboring_symbols(&numbers[1], center, over, under);
-    assert_eq!(symbols_1.len(), 1);
-    assert_eq!(symbols_1[0], &b'*');
+    let rows = [".....*....", ".664.598..", ".........."];
+    assert_eq!(neighboring_symbols(&rows, 0), []);
+    assert_eq!(neighboring_symbols(&rows, 1), [b'*']);
 }
 
 #[test]
 fn test_has_symbol_under() {
-    let over = None;
-    let center = "467..114..".as_bytes();
-    let under = Some("...*......".as_bytes());
-    let numbers = Parser::new(center).collect::<Vec<_>>();
-    assert_eq!(numbers.len(), 2);
-
-    let symbols_0 = find_neighboring_symbols(&numbers[0], center, over, under);
-    assert_eq!(symbols_0.len(), 1);
-    assert_eq!(symbols_0[0], &b'*');
-
-    let symbols_1 = find_neighboring_symbols(&numbers[1], center, over, under);
-    assert_eq!(symbols_1.len(), 0);
+    let rows = ["..........", "467..114..", "...*......"];
+    assert_eq!(neighboring_symbols(&rows, 0), [b'*']);
+    assert_eq!(neighboring_symbols(&rows, 1), []);
+}
+
+#[test]
+fn test_example() {
+    let schematic = Schematic::parse(EXAMPLE).unwrap();
+    assert_eq!(schematic.part_numbers().map(|n| n.value).sum::<i64>(), 4361);
+    assert_eq!(
+        schematic
+            .loose_numbers()
+            .map(|n| n.value)
+            .collect::<Vec<_>>(),
+        [114, 58]
+    );
+
+    let gears = schematic
+        .gears(b'*', 2)
+        .map(|(s, numbers)| {
+            (
+                (s.x, s.y),
+                numbers.iter().map(|n| n.value).collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(gears, [((3, 1), vec![467, 35]), ((5, 8), vec![755, 598])]);
+
+    let stars = schematic
+        .parts_adjacent(b'*')
+        .map(|(_, numbers)| numbers.len())
+        .collect::<Vec<_>>();
+    assert_eq!(stars, [2, 1, 2]);
+    assert_eq!(schematic.gears(b'*', 1).count(), 1);
+    assert_eq!(
+        schematic.parts_adjacent(b'#').next().unwrap().1[0].value,
+        633
+    );
+    assert_eq!(schematic.parts_adjacent(b'?').count(), 0);
+}
+
+#[test]
+fn test_shared_symbol() {
+    // A symbol touched by three numbers, listed once each even when several digits touch it.
+    let schematic = Schematic::parse("1.2\n.*.\n33.\n").unwrap();
+    let (_, numbers) = schematic.parts_adjacent(b'*').next().unwrap();
+    assert_eq!(
+        numbers.iter().map(|n| n.value).collect::<Vec<_>>(),
+        [1, 2, 33]
+    );
+    assert_eq!(schematic.gears(b'*', 3).count(), 1);
+    assert_eq!(schematic.gears(b'*', 2).count(), 0);
+
+    assert_eq!(
+        Schematic::parse("...\n..\n").err(),
+        Some("Line 2 is 2 wide, expected 3".to_string())
+    );
 }
 
 #[test]
@@ -1,62 +1,23 @@
-use std::collections::HashSet;
+//! # Day 4: Scratchcards
 
-static CARD_DATA: &'static str = include_str!("day4.txt");
+use crate::scratchcard::{Card, Cascade, Scoring};
 
-#[derive(Debug, PartialEq, Eq)]
-struct Card {
-    id: i16,
-    winning_numbers: HashSet<u8>,
-    my_numbers: HashSet<u8>,
-}
+static CARD_DATA: &str = include_str!("day4.txt");
 
-fn parse_line(line: &str) -> Card {
-    let mut main_parts = line.split(":");
-    let prefix_part = main_parts.next().unwrap();
-    let numbers_part = main_parts.next().unwrap();
-
-    let prefix = prefix_part
-        .trim_start_matches("Card")
-        .trim_start_matches(' ');
-    let id = prefix.parse::<i16>().unwrap();
-
-    let mut numbers_parts = numbers_part.split("|");
-    let winning_numbers_part = numbers_parts.next().unwrap();
-    let my_numbers_part = numbers_parts.next().unwrap();
-
-    let winning_numbers = winning_numbers_part
-        .split_whitespace()
-        .into_iter()
-        .filter(|n| n.len() > 0)
-        .map(|n| n.parse::<u8>().unwrap())
-        .collect::<HashSet<_>>();
-
-    let my_numbers = my_numbers_part
-        .split_whitespace()
-        .into_iter()
-        .filter(|n| n.len() > 0)
-        .map(|n| n.parse::<u8>().unwrap())
-        .collect::<HashSet<_>>();
-
-    Card {
-        id,
-        winning_numbers,
-        my_numbers,
-    }
+fn parse(card_data: &str) -> Result<Vec<Card>, String> {
+    card_data
+        .lines()
+        .enumerate()
+        .map(|(i, line)| Card::parse(line).map_err(|e| format!("Line {}: {}", i + 1, e)))
+        .collect()
 }
 
 fn solve_part1(card_data: &'static str) -> i64 {
-    card_data
-        .lines()
-        .map(|l| parse_line(l))
-        .map(|card| {
-            let count = card.my_numbers.intersection(&card.winning_numbers).count() as i64;
-            if count == 0 {
-                count
-            } else {
-                1 << count - 1
-            }
-        })
-        .sum::<i64>()
+    parse(card_data)
+        .unwrap()
+        .iter()
+        .map(|card| Scoring::Doubling.score(card.matches()))
+        .sum::<u64>() as i64
 }
 
 pub fn part1() -> i64 {
@@ -64,35 +25,66 @@ pub fn part1() -> i64 {
 }
 
 fn solve_part2(card_data: &'static str) -> i64 {
-    let cards = card_data.lines().map(|l| parse_line(l)).collect::<Vec<_>>();
-
-    let mut counts = vec![1i64; cards.len()];
-
-    for i in 0..cards.len() {
-        let card = &cards[i];
-        let card_count = counts[i];
-
-        let winning_number_count = card.my_numbers.intersection(&card.winning_numbers).count();
-
-        let start = std::cmp::min(cards.len(), i + 1);
-        let end = std::cmp::min(cards.len(), i + winning_number_count + 1);
-        for j in start..end {
-            counts[j] += card_count;
-        }
-    }
-
-    counts.into_iter().sum()
+    Cascade::run(&parse(card_data).unwrap()).total() as i64
 }
 
 pub fn part2() -> i64 {
     solve_part2(CARD_DATA)
 }
 
+/// Explain the score and copies of each card of the puzzle input or `FILE`, scored by doubling
+/// or, with `--linear`, one point per match.
+pub fn report(args: &[String]) -> Result<(), String> {
+    let (scoring, args) = match args {
+        [flag, rest @ ..] if flag == "--linear" => (Scoring::Linear, rest),
+        _ => (Scoring::Doubling, args),
+    };
+    let input = match args {
+        [] => CARD_DATA.to_string(),
+        [file] => std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?,
+        _ => return Err("Usage: scratchcards [--linear] [FILE]".to_string()),
+    };
+
+    let cards = parse(&input)?;
+    let cascade = Cascade::run(&cards);
+    let mut points = 0u64;
+    for (i, card) in cards.iter().enumerate() {
+        let score = scoring.score(card.matches());
+        points = points.saturating_add(score);
+        let from = cascade.received[i]
+            .iter()
+            .map(|&(j, copies)| format!("{} from card {}", copies, cards[j].id))
+            .collect::<Vec<_>>();
+        println!(
+            "Card {:>3}: {:>2} matches, {:>4} points, {:>8} held, won {:>8} copies{}",
+            card.id,
+            card.matches(),
+            score,
+            cascade.copies[i],
+            cascade.produced[i],
+            match from.is_empty() {
+                true => String::new(),
+                false => format!(", received {}", from.join(", ")),
+            }
+        );
+    }
+    let saturated = |n: u64| match n {
+        u64::MAX => format!("{} (saturated)", n),
+        n => n.to_string(),
+    };
+    println!(
+        "Points: {}, cards: {}",
+        saturated(points),
+        saturated(cascade.total())
+    );
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
-    static TEST_CARDS: &'static str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53\n\
+    static TEST_CARDS: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53\n\
     Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19\n\
     Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1\n\
     Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83\n\
@@ -104,13 +96,11 @@ mod test {
         let line: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17 9 48 53";
         let card = Card {
             id: 1,
-            winning_numbers: vec![41, 48, 83, 86, 17].into_iter().collect::<HashSet<_>>(),
-            my_numbers: vec![83, 86, 6, 31, 17, 9, 48, 53]
-                .into_iter()
-                .collect::<HashSet<_>>(),
+            winning: [41, 48, 83, 86, 17].into_iter().collect(),
+            numbers: [83, 86, 6, 31, 17, 9, 48, 53].into_iter().collect(),
         };
 
-        assert_eq!(parse_line(line), card);
+        assert_eq!(parse(line), Ok(vec![card]));
     }
 
     #[test]
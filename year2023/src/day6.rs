@@ -1,3 +1,5 @@
+//! # Day 6: Wait For It
+
 fn load() -> (Vec<i64>, Vec<i64>) {
     let data = include_str!("day6.txt");
     let mut lines = data.lines();
@@ -31,7 +33,7 @@ pub fn part1() -> i64 {
     let (time, distance) = load();
 
     time.into_iter()
-        .zip(distance.into_iter())
+        .zip(distance)
         .map(|(time, distance)| count_winning_races(time, distance))
         .product()
 }
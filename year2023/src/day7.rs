@@ -1,7 +1,9 @@
+//! # Day 7: Camel Cards
+
 use std::collections::BTreeMap;
 use std::{fmt, str::FromStr};
 
-static DATA: &'static str = include_str!("day7.txt");
+static DATA: &str = include_str!("day7.txt");
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
 enum Card {
@@ -166,19 +168,19 @@ impl Hand {
             freq
         });
 
-        if freq.iter().any(|f| *f == 5) {
+        if freq.contains(&5) {
             return HandRanking::FiveOfAKind;
         }
 
-        if freq.iter().any(|f| *f == 4) {
+        if freq.contains(&4) {
             return HandRanking::FourOfAKind;
         }
 
-        if freq.iter().any(|f| *f == 3) && freq.iter().any(|f| *f == 2) {
+        if freq.contains(&3) && freq.contains(&2) {
             return HandRanking::FullHouse;
         }
 
-        if freq.iter().any(|f| *f == 3) {
+        if freq.contains(&3) {
             return HandRanking::ThreeOfAKind;
         }
 
@@ -186,7 +188,7 @@ impl Hand {
             return HandRanking::TwoPair;
         }
 
-        if freq.iter().any(|f| *f == 2) {
+        if freq.contains(&2) {
             return HandRanking::OnePair;
         }
 
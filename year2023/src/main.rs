@@ -4,8 +4,10 @@ mod day3;
 mod day4;
 mod day6;
 mod day7;
+mod matcher;
+mod scratchcard;
 
-use advent_of_code_core::{menu, Year};
+use advent_of_code_core::{menu_with_commands, Command, Year};
 
 pub static SOLUTIONS: Year = [
     (Some(day1::part1), Some(day1::part2)),
@@ -34,6 +36,31 @@ pub static SOLUTIONS: Year = [
     (None, None),
 ];
 
+advent_of_code_core::sources!(SOURCES, SOLUTIONS, [1, 2, 3, 4, 6, 7]);
+
+static COMMANDS: [Command; 4] = [
+    (
+        "calibrate",
+        "[--language=NAME]... [FILE]  show the numbers found on each day 1 line",
+        day1::report,
+    ),
+    (
+        "cubes",
+        "[--bag=\"N COLOUR, ...\"] [FILE]  check the day 2 games against a bag of cubes",
+        day2::report,
+    ),
+    (
+        "schematic",
+        "[FILE]  show the day 3 symbols, gears and numbers touching no symbol",
+        day3::report,
+    ),
+    (
+        "scratchcards",
+        "[--linear] [FILE]  explain the points and copies of each day 4 card",
+        day4::report,
+    ),
+];
+
 fn main() -> Result<(), String> {
-    menu(&SOLUTIONS)
+    menu_with_commands(&SOLUTIONS, &SOURCES, &COMMANDS)
 }
@@ -0,0 +1,162 @@
+//! Finds every occurrence of a set of words in a single pass over a text, using an Aho–Corasick
+//! automaton.
+//!
+//! The words are stored in a trie, and each state falls back to the state of its longest proper
+//! suffix that is also in the trie. Following those fallbacks up front gives a transition for
+//! every state and byte, so the text is scanned one byte at a time without backtracking, and
+//! overlapping words like the `one` in `twone` are all found.
+
+/// A word found in the text, `start..end` being its byte range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<T> {
+    pub start: usize,
+    pub end: usize,
+    pub value: T,
+}
+
+pub struct Matcher<T> {
+    /// The state reached from each state on each byte, the root being state 0.
+    next: Vec<[usize; 256]>,
+    /// The words ending in each state, longest first.
+    outputs: Vec<Vec<usize>>,
+    /// The length and value of each word.
+    words: Vec<(usize, T)>,
+}
+
+impl<T: Copy> Matcher<T> {
+    /// Build a matcher for `words`, each reported with its value. A repeated word keeps the last
+    /// value given.
+    pub fn new<'a>(words: impl IntoIterator<Item = (&'a str, T)>) -> Matcher<T> {
+        const NONE: usize = usize::MAX;
+        let mut matcher = Matcher {
+            next: vec![[NONE; 256]],
+            outputs: vec![Vec::new()],
+            words: Vec::new(),
+        };
+
+        for (word, value) in words {
+            assert!(!word.is_empty(), "can not match an empty word");
+            let mut state = 0;
+            for b in word.bytes() {
+                if matcher.next[state][b as usize] == NONE {
+                    matcher.next[state][b as usize] = matcher.next.len();
+                    matcher.next.push([NONE; 256]);
+                    matcher.outputs.push(Vec::new());
+                }
+                state = matcher.next[state][b as usize];
+            }
+            match matcher.outputs[state].first() {
+                Some(&w) => matcher.words[w].1 = value,
+                None => {
+                    matcher.outputs[state].push(matcher.words.len());
+                    matcher.words.push((word.len(), value));
+                }
+            }
+        }
+
+        // Breadth first, so the fallback of a state is complete before the state itself.
+        let mut fallback = vec![0; matcher.next.len()];
+        let mut queue = std::collections::VecDeque::new();
+        for b in 0..256 {
+            match matcher.next[0][b] {
+                NONE => matcher.next[0][b] = 0,
+                child => queue.push_back(child),
+            }
+        }
+        while let Some(state) = queue.pop_front() {
+            for b in 0..256 {
+                let via_fallback = matcher.next[fallback[state]][b];
+                match matcher.next[state][b] {
+                    NONE => matcher.next[state][b] = via_fallback,
+                    child => {
+                        fallback[child] = via_fallback;
+                        let inherited = matcher.outputs[via_fallback].clone();
+                        matcher.outputs[child].extend(inherited);
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+        matcher
+    }
+
+    /// Every occurrence of the words in `text`, ordered by where they end, and the longest
+    /// first of the words ending in the same place.
+    pub fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = Match<T>> + 'a {
+        text.bytes()
+            .enumerate()
+            .scan(0, |state, (i, b)| {
+                *state = self.next[*state][b as usize];
+                Some((i + 1, *state))
+            })
+            .flat_map(move |(end, state)| {
+                self.outputs[state].iter().map(move |&w| {
+                    let (len, value) = self.words[w];
+                    Match {
+                        start: end - len,
+                        end,
+                        value,
+                    }
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn find(matcher: &Matcher<usize>, text: &str) -> Vec<(usize, usize, usize)> {
+        matcher
+            .find_iter(text)
+            .map(|m| (m.start, m.end, m.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let matcher = Matcher::new([("he", 0), ("she", 1), ("his", 2), ("hers", 3)]);
+        assert_eq!(find(&matcher, "ushers"), [(1, 4, 1), (2, 4, 0), (2, 6, 3)]);
+        assert_eq!(find(&matcher, "hishe"), [(0, 3, 2), (2, 5, 1), (3, 5, 0)]);
+        assert_eq!(find(&matcher, ""), []);
+        assert_eq!(find(&matcher, "xyz"), []);
+
+        let matcher = Matcher::new([("two", 2), ("one", 1), ("eight", 8)]);
+        assert_eq!(
+            find(&matcher, "twoneightwo"),
+            [(0, 3, 2), (2, 5, 1), (4, 9, 8), (8, 11, 2)]
+        );
+    }
+
+    #[test]
+    fn test_brute_force() {
+        let words = ["a", "ab", "bab", "bc", "bca", "c", "caa"];
+        let matcher = Matcher::new(words.iter().enumerate().map(|(i, w)| (*w, i)));
+        let text = "abccabbcaabcbabcabaacbca";
+
+        let mut expected = Vec::new();
+        for end in 1..=text.len() {
+            for (i, w) in words.iter().enumerate() {
+                if text[..end].ends_with(w) {
+                    expected.push((end - w.len(), end, i));
+                }
+            }
+        }
+        let mut found = find(&matcher, text);
+        found.sort_by_key(|&(start, end, _)| (end, start));
+        expected.sort_by_key(|&(start, end, _)| (end, start));
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_repeated_and_multibyte() {
+        let matcher = Matcher::new([("fünf", 5), ("un", 1), ("fünf", 50)]);
+        assert_eq!(find(&matcher, "fünfun"), [(0, 5, 50), (5, 7, 1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "can not match an empty word")]
+    fn test_empty_word() {
+        Matcher::new([("", 0)]);
+    }
+}
@@ -0,0 +1,206 @@
+//! Scratchcards: the numbers they match, how they are scored, and the copies won by a pile.
+
+use advent_of_code_core::bitset::BitSet;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    pub id: usize,
+    pub winning: BitSet,
+    pub numbers: BitSet,
+}
+
+/// How the matching numbers of a card are turned into points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scoring {
+    /// One point for the first match, doubled for each match after it.
+    Doubling,
+    /// One point for each match.
+    Linear,
+}
+
+/// The copies of each card in a pile, where a card with `n` matching numbers wins a copy of
+/// each of the next `n` cards, and copies win just like the originals. Cards are indexed by
+/// their position in the pile. Counts saturate at `u64::MAX`, since they roughly double with each
+/// card in a chain of wins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cascade {
+    /// The number of each card held in the end, the original included.
+    pub copies: Vec<u64>,
+    /// The number of copies of later cards won by all copies of each card.
+    pub produced: Vec<u64>,
+    /// For each card, the copies of it won by the earlier cards, as `(card, copies)`.
+    pub received: Vec<Vec<(usize, u64)>>,
+}
+
+impl Card {
+    /// The largest number a card may hold, since its bitsets grow with the largest number.
+    pub const MAX_NUMBER: usize = 9999;
+
+    /// Parse `Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53`.
+    pub fn parse(line: &str) -> Result<Card, String> {
+        let (id, numbers) = line
+            .split_once(':')
+            .ok_or(format!("Missing ':' in '{}'", line))?;
+        let id = id
+            .strip_prefix("Card")
+            .ok_or(format!("Expected 'Card ID' in '{}'", id))?
+            .trim();
+        let id = id
+            .parse::<usize>()
+            .map_err(|e| format!("Invalid card id '{}': {}", id, e))?;
+
+        let (winning, numbers) = numbers
+            .split_once('|')
+            .ok_or(format!("Missing '|' in '{}'", line))?;
+        let set = |s: &str| {
+            s.split_whitespace()
+                .map(|n| match n.parse::<usize>() {
+                    Ok(n) if n <= Card::MAX_NUMBER => Ok(n),
+                    Ok(n) => Err(format!("Number {} is larger than {}", n, Card::MAX_NUMBER)),
+                    Err(e) => Err(format!("Invalid number '{}': {}", n, e)),
+                })
+                .collect::<Result<BitSet, _>>()
+        };
+
+        Ok(Card {
+            id,
+            winning: set(winning)?,
+            numbers: set(numbers)?,
+        })
+    }
+
+    /// The number of winning numbers on the card.
+    pub fn matches(&self) -> usize {
+        let mut matching = self.numbers.clone();
+        matching.intersect_with(&self.winning);
+        matching.len()
+    }
+}
+
+impl Scoring {
+    /// The points for a card with `matches` matching numbers. Doubling saturates at `u64::MAX`
+    /// from 65 matches on.
+    pub fn score(self, matches: usize) -> u64 {
+        match (self, matches) {
+            (Scoring::Doubling, 0) => 0,
+            (Scoring::Doubling, n) => u32::try_from(n - 1)
+                .ok()
+                .and_then(|shift| 1u64.checked_shl(shift))
+                .unwrap_or(u64::MAX),
+            (Scoring::Linear, n) => n as u64,
+        }
+    }
+}
+
+impl Cascade {
+    /// Play the pile in order. Copies won past the end of the pile are lost.
+    pub fn run(cards: &[Card]) -> Cascade {
+        let mut cascade = Cascade {
+            copies: vec![1; cards.len()],
+            produced: vec![0; cards.len()],
+            received: vec![Vec::new(); cards.len()],
+        };
+
+        for (i, card) in cards.iter().enumerate() {
+            let copies = cascade.copies[i];
+            let won = (i + 1..cards.len()).take(card.matches());
+            for j in won {
+                cascade.copies[j] = cascade.copies[j].saturating_add(copies);
+                cascade.produced[i] = cascade.produced[i].saturating_add(copies);
+                cascade.received[j].push((i, copies));
+            }
+        }
+        cascade
+    }
+
+    /// The number of cards held in the end.
+    pub fn total(&self) -> u64 {
+        self.copies
+            .iter()
+            .fold(0, |total: u64, &copies| total.saturating_add(copies))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static CARDS: [&str; 6] = [
+        "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
+        "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+        "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1",
+        "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83",
+        "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36",
+        "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+    ];
+
+    fn cards() -> Vec<Card> {
+        CARDS.iter().map(|l| Card::parse(l).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_scoring() {
+        let matches = cards().iter().map(Card::matches).collect::<Vec<_>>();
+        assert_eq!(matches, [4, 2, 2, 1, 0, 0]);
+
+        let total = |scoring: Scoring| matches.iter().map(|&m| scoring.score(m)).sum::<u64>();
+        assert_eq!(total(Scoring::Doubling), 13);
+        assert_eq!(total(Scoring::Linear), 9);
+        assert_eq!(Scoring::Doubling.score(10), 512);
+        assert_eq!(Scoring::Doubling.score(64), 1 << 63);
+        assert_eq!(Scoring::Doubling.score(65), u64::MAX);
+        assert_eq!(Scoring::Doubling.score(usize::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_cascade() {
+        let cascade = Cascade::run(&cards());
+        assert_eq!(cascade.copies, [1, 2, 4, 8, 14, 1]);
+        assert_eq!(cascade.total(), 30);
+        assert_eq!(cascade.produced, [4, 4, 8, 8, 0, 0]);
+        assert_eq!(cascade.received[0], []);
+        assert_eq!(cascade.received[4], [(0, 1), (2, 4), (3, 8)]);
+
+        // Every copy past the originals was produced by an earlier card.
+        let produced = cascade.produced.iter().sum::<u64>();
+        assert_eq!(produced, cascade.total() - cascade.copies.len() as u64);
+    }
+
+    #[test]
+    fn test_cascade_past_the_end() {
+        let cards = ["Card 1: 1 2 3 | 1 2 3", "Card 2: 4 | 4"].map(|l| Card::parse(l).unwrap());
+        let cascade = Cascade::run(&cards);
+        assert_eq!(cascade.copies, [1, 2]);
+        assert_eq!(cascade.produced, [1, 0]);
+        assert_eq!(Cascade::run(&[]).total(), 0);
+    }
+
+    #[test]
+    fn test_cascade_saturates() {
+        // Each card wins the next three, so the copies grow faster than doubling.
+        let cards = (1..=100)
+            .map(|id| Card::parse(&format!("Card {}: 1 2 3 | 1 2 3", id)).unwrap())
+            .collect::<Vec<_>>();
+        let cascade = Cascade::run(&cards);
+        assert_eq!(cascade.copies[..5], [1, 2, 4, 8, 15]);
+        assert_eq!(cascade.copies[99], u64::MAX);
+        assert_eq!(cascade.produced[90], u64::MAX);
+        assert_eq!(cascade.total(), u64::MAX);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |l| Card::parse(l).unwrap_err();
+        assert_eq!(error("Card 1 1 | 2"), "Missing ':' in 'Card 1 1 | 2'");
+        assert_eq!(error("Game 1: 1 | 2"), "Expected 'Card ID' in 'Game 1'");
+        assert!(error("Card x: 1 | 2").starts_with("Invalid card id 'x': "));
+        assert_eq!(error("Card 1: 1 2"), "Missing '|' in 'Card 1: 1 2'");
+        assert!(error("Card 1: 1 | a").starts_with("Invalid number 'a': "));
+        assert_eq!(Card::parse("Card 7: 200 | 200").unwrap().matches(), 1);
+        assert_eq!(Card::parse("Card 8: 9999 | 1").unwrap().winning.len(), 1);
+        assert_eq!(
+            error("Card 9: 1 | 10000"),
+            "Number 10000 is larger than 9999"
+        );
+    }
+}
@@ -1,3 +1,5 @@
+//! # Day 1: Historian Hysteria
+
 fn load() -> (Vec<i64>, Vec<i64>) {
     let data = include_str!("day1.txt");
     let mut left = Vec::new();
@@ -22,7 +24,7 @@ pub fn part1() -> i64 {
     right.sort();
 
     left.into_iter()
-        .zip(right.into_iter())
+        .zip(right)
         .map(|(n1, n2)| (n1 - n2).abs())
         .sum()
 }
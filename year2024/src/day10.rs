@@ -1,4 +1,8 @@
-static DATA: &'static str = include_str!("day10.txt");
+//! # Day 10: Hoof It
+
+use advent_of_code_core::visual::{self, Color, Frame};
+
+static DATA: &str = include_str!("day10.txt");
 
 struct Map {
     tiles: Vec<u8>,
@@ -64,18 +68,48 @@ impl Map {
                 return score;
             }
         }
-        return 0;
+        0
+    }
+
+    /// Draw the height map with every trailhead and reachable peak found so far in yellow,
+    /// and the current trailhead and the peaks reachable from it in red and green.
+    fn draw(&self, found: &[(i16, i16)], trailhead: (i16, i16), heads: &[(i16, i16)]) -> Frame {
+        let mut frame = Frame::new(self.width as usize, self.height as usize);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let lvl = self.get_cell(x, y).unwrap();
+                let c = char::from_digit(lvl as u32, 10).unwrap();
+                frame.mark(x as i64, y as i64, c, Color::Gray);
+            }
+        }
+
+        for &(x, y) in found {
+            frame.tint(x as i64, y as i64, Color::Yellow);
+        }
+        for &(x, y) in heads {
+            frame.tint(x as i64, y as i64, Color::Green);
+        }
+        frame.tint(trailhead.0 as i64, trailhead.1 as i64, Color::Red);
+
+        frame
     }
 
     fn scores(&self) -> i64 {
         let mut score: i64 = 0;
         let mut heads = Vec::<(i16, i16)>::new();
+        let mut found = Vec::<(i16, i16)>::new();
 
         for y in 0..self.height {
             for x in 0..self.width {
                 heads.clear();
                 self.count_recursive(&mut heads, x, y, 0);
                 score += heads.len() as i64;
+
+                if visual::enabled() && !heads.is_empty() {
+                    visual::frame(|| self.draw(&found, (x, y), &heads));
+                    found.push((x, y));
+                    found.extend_from_slice(&heads);
+                }
             }
         }
 
@@ -85,11 +119,18 @@ impl Map {
     fn ratings(&self) -> i64 {
         let mut score: i64 = 0;
         let mut heads = Vec::<(i16, i16)>::new();
+        let mut found = Vec::<(i16, i16)>::new();
 
         for y in 0..self.height {
             for x in 0..self.width {
                 heads.clear();
                 score += self.count_recursive(&mut heads, x, y, 0) as i64;
+
+                if visual::enabled() && !heads.is_empty() {
+                    visual::frame(|| self.draw(&found, (x, y), &heads));
+                    found.push((x, y));
+                    found.extend_from_slice(&heads);
+                }
             }
         }
 
@@ -108,18 +149,18 @@ fn solve_part2(data: &str) -> i64 {
 }
 
 pub fn part1() -> i64 {
-    solve_part1(&DATA)
+    solve_part1(DATA)
 }
 
 pub fn part2() -> i64 {
-    solve_part2(&DATA)
+    solve_part2(DATA)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    static EXAMPLE: &'static str = "89010123\n\
+    static EXAMPLE: &str = "89010123\n\
         78121874\n\
         87430965\n\
         96549874\n\
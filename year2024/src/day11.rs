@@ -1,16 +1,18 @@
-use std::collections::HashMap;
+//! # Day 11: Plutonian Pebbles
 
-static DATA: &'static str = include_str!("day11.txt");
+use advent_of_code_core::hash::FxHashMap;
+
+static DATA: &str = include_str!("day11.txt");
 
 fn base10_digits(n: u64) -> usize {
     (n as f64).log10().floor() as usize + 1
 }
 
 fn step(
-    stones: HashMap<u64, u64>,
-    cache: &mut HashMap<u64, Option<(u64, u64)>>,
-) -> HashMap<u64, u64> {
-    let mut new = HashMap::<u64, u64>::with_capacity(stones.len());
+    stones: FxHashMap<u64, u64>,
+    cache: &mut FxHashMap<u64, Option<(u64, u64)>>,
+) -> FxHashMap<u64, u64> {
+    let mut new = FxHashMap::<u64, u64>::with_capacity_and_hasher(stones.len(), Default::default());
     for (stone, count) in stones.into_iter() {
         if stone == 0 {
             *new.entry(1).or_default() += count;
@@ -19,7 +21,7 @@ fn step(
 
         let cached = cache.entry(stone).or_insert_with(|| {
             let digets: usize = base10_digits(stone);
-            if digets % 2 == 0 {
+            if digets.is_multiple_of(2) {
                 let m = 10f64.powi(digets as i32 / 2) as u64;
                 Some((stone / m, stone % m))
             } else {
@@ -39,7 +41,7 @@ fn step(
     new
 }
 
-fn load(data: &str) -> HashMap<u64, u64> {
+fn load(data: &str) -> FxHashMap<u64, u64> {
     let stones: Vec<u64> = data
         .split_whitespace()
         .map(|s| s.parse::<u64>().unwrap())
@@ -48,26 +50,26 @@ fn load(data: &str) -> HashMap<u64, u64> {
     stones
         .into_iter()
         .map(|n| (n, 1))
-        .collect::<HashMap<u64, u64>>()
+        .collect::<FxHashMap<u64, u64>>()
 }
 
 fn solve(data: &str, steps: usize) -> i64 {
     let mut stones = load(data);
 
-    let mut cache = HashMap::new();
+    let mut cache = FxHashMap::default();
     for _ in 0..steps {
         stones = step(stones, &mut cache);
     }
 
-    stones.into_iter().map(|(_, count)| count as i64).sum()
+    stones.into_values().map(|count| count as i64).sum()
 }
 
 pub fn part1() -> i64 {
-    solve(&DATA, 25)
+    solve(DATA, 25)
 }
 
 pub fn part2() -> i64 {
-    solve(&DATA, 75)
+    solve(DATA, 75)
 }
 
 #[cfg(test)]
@@ -76,13 +78,13 @@ mod test {
 
     use super::*;
 
-    static EXAMPLE_1: &'static str = "0 1 10 99 999";
-    static EXAMPLE_2: &'static str = "125 17";
+    static EXAMPLE_1: &str = "0 1 10 99 999";
+    static EXAMPLE_2: &str = "125 17";
 
-    fn map_to_vec(stones: &HashMap<u64, u64>) -> Vec<u64> {
+    fn map_to_vec(stones: &FxHashMap<u64, u64>) -> Vec<u64> {
         let mut res = stones
-            .into_iter()
-            .flat_map(|(stone, count)| iter::repeat(*stone).take(*count as usize))
+            .iter()
+            .flat_map(|(stone, count)| iter::repeat_n(*stone, *count as usize))
             .collect::<Vec<u64>>();
         res.sort();
         res
@@ -91,7 +93,7 @@ mod test {
     #[test]
     fn test_example_1() {
         let mut stones = load(EXAMPLE_1);
-        let mut cache = HashMap::new();
+        let mut cache = FxHashMap::default();
         stones = step(stones, &mut cache);
         assert_eq!(map_to_vec(&stones), &[0, 1, 1, 9, 9, 2024, 2021976]);
     }
@@ -118,7 +120,7 @@ mod test {
     #[test]
     fn test_step() {
         let mut stones = load("10");
-        let mut cache = HashMap::new();
+        let mut cache = FxHashMap::default();
 
         stones = step(stones, &mut cache);
         assert_eq!(map_to_vec(&stones), &[0, 1]);
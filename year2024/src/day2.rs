@@ -1,3 +1,5 @@
+//! # Day 2: Red-Nosed Reports
+
 fn load() -> Vec<Vec<i16>> {
     let data = include_str!("day2.txt");
 
@@ -23,11 +25,11 @@ fn is_safe_remove(levels: &[i16], buffer: &mut Vec<i16>) -> bool {
     for i in 0..levels.len() {
         buffer.clear();
 
-        for j in 0..levels.len() {
+        for (j, level) in levels.iter().enumerate() {
             if i == j {
                 continue;
             }
-            buffer.push(levels[j]);
+            buffer.push(*level);
         }
 
         if is_safe(&buffer[..]) {
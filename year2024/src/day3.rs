@@ -1,3 +1,5 @@
+//! # Day 3: Mull It Over
+
 use regex::Regex;
 
 enum Instruction {
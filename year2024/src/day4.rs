@@ -1,6 +1,6 @@
-use std::usize;
+//! # Day 4: Ceres Search
 
-static DATA: &'static str = include_str!("day4.txt");
+static DATA: &str = include_str!("day4.txt");
 
 struct Board {
     data: Vec<char>,
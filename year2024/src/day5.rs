@@ -1,15 +1,17 @@
-static DATA: &'static str = include_str!("day5.txt");
+//! # Day 5: Print Queue
+
+static DATA: &str = include_str!("day5.txt");
 
 fn rules_iter<'a>(rules: &'a [(i16, i16)], n: i16) -> impl Iterator<Item = i16> + 'a {
     rules.iter().filter_map(move |o| match o {
-        (n1, n2) if *n2 == n => Some(n1.clone()),
+        (n1, n2) if *n2 == n => Some(*n1),
         _ => None,
     })
 }
 
 fn rules_iter_reverse<'a>(rules: &'a [(i16, i16)], n: i16) -> impl Iterator<Item = i16> + 'a {
     rules.iter().filter_map(move |o| match o {
-        (n1, n2) if *n1 == n => Some(n2.clone()),
+        (n1, n2) if *n1 == n => Some(*n2),
         _ => None,
     })
 }
@@ -27,8 +29,8 @@ fn is_valid_update(update: &[i16], rules: &[(i16, i16)]) -> bool {
 
 fn find_next_candidate(candidates: &[i16], rules: &[(i16, i16)]) -> Option<usize> {
     'candidates: for (i, n) in candidates.iter().enumerate() {
-        for r in rules_iter_reverse(&rules, *n) {
-            if candidates.iter().any(|m| r == *m) {
+        for r in rules_iter_reverse(rules, *n) {
+            if candidates.contains(&r) {
                 continue 'candidates;
             }
         }
@@ -40,7 +42,7 @@ fn find_next_candidate(candidates: &[i16], rules: &[(i16, i16)]) -> Option<usize
 fn reorder_update(update: &mut Vec<i16>, rules: &[(i16, i16)]) {
     let mut new = Vec::with_capacity(update.len());
 
-    while update.len() > 0 {
+    while !update.is_empty() {
         let next = find_next_candidate(&update[..], rules).expect("No candidate found");
 
         new.push(update.remove(next));
@@ -118,7 +120,7 @@ pub fn part2() -> i64 {
 mod test {
     use super::*;
 
-    static EXAMPLE: &'static str = "47|53\n\
+    static EXAMPLE: &str = "47|53\n\
         97|13\n\
         97|61\n\
         97|47\n\
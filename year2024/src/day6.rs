@@ -1,8 +1,10 @@
-use std::collections::HashSet;
+//! # Day 6: Guard Gallivant
 
+use advent_of_code_core::bitset::{BitSet, GridSet};
+use advent_of_code_core::visual::{self, Color, Frame};
 use bit_vec::BitVec;
 
-static DATA: &'static str = include_str!("day6.txt");
+static DATA: &str = include_str!("day6.txt");
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Direction {
@@ -12,6 +14,17 @@ enum Direction {
     Left,
 }
 
+impl Direction {
+    fn symbol(self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 struct Position {
     x: i16,
@@ -74,6 +87,11 @@ impl Position {
         self.y as usize * map.width + self.x as usize
     }
 
+    /// Unique index of both the tile and the direction, used for loop detection.
+    fn state_index(&self, map: &Map) -> usize {
+        self.index(map) * 4 + self.dir as usize
+    }
+
     fn out_of_bounds(&self, map: &Map) -> bool {
         if self.x < 0 || self.x >= map.width as i16 {
             return true;
@@ -82,7 +100,7 @@ impl Position {
         if self.y < 0 || self.y >= map.height as i16 {
             return true;
         }
-        return false;
+        false
     }
 }
 
@@ -143,39 +161,78 @@ fn step(map: &Map, pos: Position) -> Option<Position> {
         return Some(pos.turn());
     }
 
-    return Some(next);
+    Some(next)
+}
+
+/// Draw the map with the path taken by the guard and the guard's current position.
+fn draw(map: &Map, history: &[Position]) -> Frame {
+    let mut frame = Frame::new(map.width, map.height);
+    for (i, tile) in map.tiles.iter().enumerate() {
+        if tile {
+            frame.mark(
+                (i % map.width) as i64,
+                (i / map.width) as i64,
+                '#',
+                Color::Gray,
+            );
+        }
+    }
+
+    frame.path(
+        history.iter().map(|p| (p.x as i64, p.y as i64)),
+        'X',
+        Color::Yellow,
+    );
+
+    if let Some(guard) = history.last() {
+        frame.mark(
+            guard.x as i64,
+            guard.y as i64,
+            guard.dir.symbol(),
+            Color::Red,
+        );
+    }
+
+    frame
 }
 
-fn count_distinct_positions(history: &[Position]) -> i64 {
-    history
-        .iter()
-        .map(|p| (p.x, p.y))
-        .collect::<HashSet<(i16, i16)>>()
-        .len() as i64
+fn count_distinct_positions(map: &Map, history: &[Position]) -> i64 {
+    let mut visited = GridSet::new(map.width, map.height);
+    for p in history {
+        visited.insert(p.x as i64, p.y as i64);
+    }
+    visited.len() as i64
 }
 
-fn detect_loop(history: &[Position], pos: Position) -> bool {
-    history.contains(&pos)
+/// Record `pos` as visited, returning `true` if it has been visited before.
+fn detect_loop(visited: &mut BitSet, map: &Map, pos: Position) -> bool {
+    !visited.insert(pos.state_index(map))
 }
 
 fn solve_part1(data: &str) -> i64 {
     let (map, mut pos) = load(data);
     let mut history: Vec<Position> = Vec::new();
+    let mut visited = BitSet::with_capacity(map.tiles.len() * 4);
     history.push(pos);
+    detect_loop(&mut visited, &map, pos);
 
     while let Some(new_pos) = step(&map, pos) {
-        assert!(!detect_loop(&history, new_pos));
+        let is_loop = detect_loop(&mut visited, &map, new_pos);
+        assert!(!is_loop);
         history.push(new_pos);
         pos = new_pos;
+
+        visual::frame(|| draw(&map, &history));
     }
 
-    count_distinct_positions(&history[..])
+    count_distinct_positions(&map, &history[..])
 }
 
 fn solve_part2(data: &str) -> i64 {
     let (mut map, start_pos) = load(data);
 
-    let mut history: Vec<Position> = Vec::new();
+    let mut visited = BitSet::with_capacity(map.tiles.len() * 4);
+    let mut obstructions = Vec::new();
 
     let mut loop_count = 0;
     for y in 0..map.width {
@@ -186,18 +243,26 @@ fn solve_part2(data: &str) -> i64 {
             }
 
             let mut pos = start_pos;
-            history.clear();
-            history.push(pos);
+            visited.clear();
+            detect_loop(&mut visited, &map, pos);
 
             map.tiles.set(idx, true);
 
             while let Some(new_pos) = step(&map, pos) {
-                if detect_loop(&history, new_pos) {
+                if detect_loop(&mut visited, &map, new_pos) {
                     loop_count += 1;
+
+                    if visual::enabled() {
+                        obstructions.push((x as i64, y as i64));
+                        visual::frame(|| {
+                            let mut frame = draw(&map, &[start_pos]);
+                            frame.path(obstructions.iter().copied(), 'O', Color::Green);
+                            frame
+                        });
+                    }
                     break;
                 }
 
-                history.push(new_pos);
                 pos = new_pos;
             }
 
@@ -209,18 +274,18 @@ fn solve_part2(data: &str) -> i64 {
 }
 
 pub fn part1() -> i64 {
-    solve_part1(&DATA)
+    solve_part1(DATA)
 }
 
 pub fn part2() -> i64 {
-    solve_part2(&DATA)
+    solve_part2(DATA)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    static EXAMPLE: &'static str = "....#.....\n\
+    static EXAMPLE: &str = "....#.....\n\
         .........#\n\
         ..........\n\
         ..#.......\n\
@@ -257,9 +322,7 @@ mod test {
         assert_eq!(part1(), 4819);
     }
 
-    // Test is slow
-    //#[test]
-    #[allow(dead_code)]
+    #[test]
     fn test_part2() {
         assert_eq!(part2(), 1796);
     }
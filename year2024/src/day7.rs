@@ -1,4 +1,6 @@
-static DATA: &'static str = include_str!("day7.txt");
+//! # Day 7: Bridge Repair
+
+static DATA: &str = include_str!("day7.txt");
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Operator {
@@ -134,18 +136,18 @@ fn solve(data: &str, concatenation: bool) -> i64 {
 }
 
 pub fn part1() -> i64 {
-    solve(&DATA, false)
+    solve(DATA, false)
 }
 
 pub fn part2() -> i64 {
-    solve(&DATA, true)
+    solve(DATA, true)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    static EXAMPLE: &'static str = "190: 10 19\n\
+    static EXAMPLE: &str = "190: 10 19\n\
         3267: 81 40 27\n\
         83: 17 5\n\
         156: 15 6\n\
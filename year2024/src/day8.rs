@@ -1,6 +1,10 @@
+//! # Day 8: Resonant Collinearity
+
 use std::collections::{BTreeMap, HashSet};
 
-static DATA: &'static str = include_str!("day8.txt");
+use advent_of_code_core::visual::{self, Color, Frame};
+
+static DATA: &str = include_str!("day8.txt");
 
 struct PairIterator<'a, T: 'a>(&'a [T], usize, usize);
 
@@ -93,6 +97,26 @@ fn load(data: &str) -> Map {
 }
 
 impl Map {
+    /// Draw the antennas, the antinodes found so far and the antenna pair currently checked.
+    fn draw(&self, antinodes: &HashSet<V2>, pair: (V2, V2)) -> Frame {
+        let mut frame = Frame::new(self.width as usize, self.height as usize);
+        for a in antinodes {
+            frame.mark(a.0 as i64, a.1 as i64, '#', Color::Magenta);
+        }
+
+        for (c, antennas) in self.antennas.iter() {
+            for a in antennas {
+                frame.mark(a.0 as i64, a.1 as i64, *c, Color::Cyan);
+            }
+        }
+
+        for a in [pair.0, pair.1] {
+            frame.tint(a.0 as i64, a.1 as i64, Color::Red);
+        }
+
+        frame
+    }
+
     fn find_first_antinode(&self) -> HashSet<V2> {
         let mut antinodes = HashSet::new();
 
@@ -111,6 +135,8 @@ impl Map {
                 {
                     antinodes.insert(a2);
                 }
+
+                visual::frame(|| self.draw(&antinodes, (*v1, *v2)));
             }
         }
 
@@ -129,6 +155,8 @@ impl Map {
                 for a2 in V2::antinodes(*v2, *v1, self.width, self.height) {
                     antinodes.insert(a2);
                 }
+
+                visual::frame(|| self.draw(&antinodes, (*v1, *v2)));
             }
         }
 
@@ -149,18 +177,18 @@ fn solve_part2(data: &str) -> i64 {
 }
 
 pub fn part1() -> i64 {
-    solve_part1(&DATA)
+    solve_part1(DATA)
 }
 
 pub fn part2() -> i64 {
-    solve_part2(&DATA)
+    solve_part2(DATA)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    static EXAMPLE_1: &'static str = "............\n\
+    static EXAMPLE_1: &str = "............\n\
         ........0...\n\
         .....0......\n\
         .......0....\n\
@@ -173,7 +201,7 @@ mod test {
         ............\n\
         ............";
 
-    static EXAMPLE_2: &'static str = "T.........\n\
+    static EXAMPLE_2: &str = "T.........\n\
         ...T......\n\
         .T........\n\
         ..........\n\
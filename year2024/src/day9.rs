@@ -1,4 +1,10 @@
-static DATA: &'static str = include_str!("day9.txt");
+//! # Day 9: Disk Fragmenter
+
+use std::ops::Range;
+
+use advent_of_code_core::interval::IntervalSet;
+
+static DATA: &str = include_str!("day9.txt");
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Block {
@@ -17,10 +23,10 @@ fn load(data: &str) -> Vec<Block> {
         let n = c.to_digit(10).unwrap() as usize;
 
         if used {
-            blocks.extend(std::iter::repeat(Block::Used(id)).take(n));
+            blocks.extend(std::iter::repeat_n(Block::Used(id), n));
             id += 1;
         } else {
-            blocks.extend(std::iter::repeat(Block::Free).take(n));
+            blocks.extend(std::iter::repeat_n(Block::Free, n));
         }
 
         used = !used;
@@ -29,7 +35,7 @@ fn load(data: &str) -> Vec<Block> {
     blocks
 }
 
-fn defragment_simple(disk: &mut Vec<Block>) {
+fn defragment_simple(disk: &mut [Block]) {
     let mut i = 0;
     let mut j = disk.len() - 1;
     while i < j {
@@ -47,61 +53,39 @@ fn defragment_simple(disk: &mut Vec<Block>) {
     }
 }
 
-fn defragment_chunks(disk: &mut Vec<Block>) {
-    //TODO: This is shit code.
-
-    let mut j = usize::MAX;
-    'main: loop {
-        let mut chunks = disk
-            .chunk_by_mut(|a, b| match (a, b) {
-                (Block::Used(a), Block::Used(b)) => a == b,
-                (Block::Free, Block::Free) => true,
-                _ => false,
-            })
-            .collect::<Vec<_>>();
-
-        j = usize::min(j, chunks.len() - 1);
-
-        let (a, b) = chunks.split_at_mut(j);
-        let used_chunk: &mut [Block] = &mut b[0];
-        if let Block::Free = used_chunk[0] {
-            if j == 0 {
-                break 'main;
-            }
-
-            j -= 1;
-            continue 'main;
-        }
+fn defragment_chunks(disk: &mut [Block]) {
+    let mut files: Vec<Range<i64>> = Vec::new();
+    let mut free = IntervalSet::new();
 
-        for i in 0..j {
-            let free_chunk = &mut a[i];
-            if let Block::Used(..) = free_chunk[0] {
-                continue;
-            }
-
-            if used_chunk.len() < free_chunk.len() {
-                let free_chunk: &mut [Block] = &mut free_chunk[..used_chunk.len()];
-                free_chunk.swap_with_slice(used_chunk);
-                continue 'main;
-            }
-
-            if used_chunk.len() == free_chunk.len() {
-                free_chunk.swap_with_slice(used_chunk);
-
-                if j == 0 {
-                    break 'main;
-                }
-                j -= 1;
-
-                continue 'main;
-            }
+    for (i, block) in disk.iter().enumerate() {
+        let i = i as i64;
+        match block {
+            Block::Used(id) if *id as usize == files.len() => files.push(i..i + 1),
+            Block::Used(id) => files[*id as usize].end = i + 1,
+            Block::Free => free.insert(i..i + 1),
         }
+    }
 
-        if j == 0 {
-            break 'main;
+    // Move each file once, in order of decreasing file id, to the leftmost span of free
+    // blocks that can fit the whole file.
+    for file in files.iter_mut().rev() {
+        let len = file.end - file.start;
+        let target = free
+            .iter()
+            .take_while(|span| span.start < file.start)
+            .find(|span| span.end - span.start >= len)
+            .map(|span| span.start);
+
+        if let Some(start) = target {
+            free.remove(start..start + len);
+            free.insert(file.clone());
+            *file = start..start + len;
         }
+    }
 
-        j -= 1;
+    disk.fill(Block::Free);
+    for (id, file) in files.into_iter().enumerate() {
+        disk[file.start as usize..file.end as usize].fill(Block::Used(id as u16));
     }
 }
 
@@ -128,18 +112,18 @@ fn solve_part2(data: &str) -> i64 {
 }
 
 pub fn part1() -> i64 {
-    solve_part1(&DATA)
+    solve_part1(DATA)
 }
 
 pub fn part2() -> i64 {
-    solve_part2(&DATA)
+    solve_part2(DATA)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    static EXAMPLE: &'static str = "2333133121414131402";
+    static EXAMPLE: &str = "2333133121414131402";
 
     #[test]
     fn test_load_1() {
@@ -189,9 +173,7 @@ mod test {
         assert_eq!(part1(), 6382875730645);
     }
 
-    // Test is slow
-    //#[test]
-    #[allow(dead_code)]
+    #[test]
     fn test_part2() {
         assert_eq!(part2(), 6420913943576);
     }
@@ -39,6 +39,8 @@ pub static SOLUTIONS: Year = [
     (None, None),
 ];
 
+advent_of_code_core::sources!(SOURCES, SOLUTIONS, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+
 fn main() -> Result<(), String> {
-    menu(&SOLUTIONS)
+    menu(&SOLUTIONS, &SOURCES)
 }